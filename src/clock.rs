@@ -0,0 +1,21 @@
+// Monotonic time from the system tick. Unlike the wall clock this never jumps (the console can resync its clock
+// mid-batch), so it's what every duration and deadline is measured with. The wall clock in `outcome::now_ms` is
+// only for file names and timestamps.
+
+extern "C" {
+    #[link_name = "\u{1}_ZN2nn2os13GetSystemTickEv"]
+    fn get_system_tick() -> u64;
+
+    #[link_name = "\u{1}_ZN2nn2os22GetSystemTickFrequencyEv"]
+    fn get_system_tick_frequency() -> u64;
+}
+
+// Microseconds since boot. Also safe to line frames up against captured video with.
+pub fn monotonic_us() -> u64 {
+    unsafe { (get_system_tick() as u128 * 1_000_000 / get_system_tick_frequency() as u128) as u64 }
+}
+
+// Milliseconds since boot.
+pub fn monotonic_ms() -> u128 {
+    monotonic_us() as u128 / 1000
+}
@@ -1,86 +1,149 @@
 use skyline::nn::{self, hid::NpadHandheldState};
-use rand::{self, Rng};
 
 use crate::navigation::{self, CurrentNavigation};
+use crate::playaid;
+use crate::scheduler::{self, Duration, Schedule};
+use crate::trace;
+use crate::watchdog;
+
+// Keys to use for input
+const KEY_RIGHT: u64 = 0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0100_0000_0000_0000;
+const KEY_DOWN: u64 = 0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_1000_0000_0000_0000;
+const KEY_UP: u64 = 0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0010_0000_0000_0000;
+const KEY_X: u64 = 0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0100;
+const KEY_B: u64 = 0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0010;
+const KEY_A: u64 = 0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0001;
+const KEY_START: u64 = 0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0100_0000_0000;
+
+// Need to space apart presses so it does not seem like we are holding the button.
+const TAP_PRESS: Duration = Duration::Frames(6);
+const TAP_RELEASE: Duration = Duration::Frames(12);
+
+// How long to let a screen settle before a one-off input.
+const SETTLE_DELAY: Duration = Duration::Ms(1000);
+
+// The schedule for the state we're currently in. It restarts whenever NAV changes.
+static mut SCHEDULE: Option<(CurrentNavigation, Schedule)> = None;
 
 pub fn handle_get_npad_state_start(
     state: *mut NpadHandheldState,
     _controller_id: *const u32,
 ) {
     unsafe {
+        watchdog::check();
+        if playaid::DAEMON_MODE {
+            playaid::poll_manifest();
+        }
         handle_menu_navigate(state);
     }
 }
 
-unsafe fn handle_menu_navigate(state: *mut NpadHandheldState) {
-    // Keys to use for input
-    let key_right: u64 = 0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0100_0000_0000_0000;
-    let key_down: u64 = 0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_1000_0000_0000_0000;
-    let key_up: u64 = 0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0010_0000_0000_0000;
-    let key_x: u64 = 0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0100;
-    let key_b: u64 = 0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0010;
-    let key_a: u64 = 0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0001;
-    let key_start: u64 = 0b0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0100_0000_0000;
-
-    let mut key_to_press: u64 = 0;
-    let mut key_to_alternate: u64 = 0;
-    let mut key_to_hold: u64 = 0;
-
-    if navigation::NAV == CurrentNavigation::ScVideo {
-        println!("[input] Pressing B to get out of video playback");
-        key_to_press = key_b;
-    } else if navigation::NAV == CurrentNavigation::ScPlayback
-    || navigation::NAV == CurrentNavigation::DoneHoverPlay {
-        nn::oe::ReportUserIsActive(); // prevent switch from dimming
-        key_to_press = key_b;
-    } else if navigation::NAV == CurrentNavigation::MainOnMelee 
-    || navigation::NAV == CurrentNavigation::MainInOnline
-    {
-        key_to_press = key_down;
-    } else if navigation::NAV == CurrentNavigation::MainOnSpirits {
-        key_to_press = key_right;
-    } else if navigation::NAV == CurrentNavigation::ScWaitingForLoad {
-        key_to_press = key_x;
-    } else if navigation::NAV == CurrentNavigation::MainOnOnline ||
-    navigation::NAV == CurrentNavigation::MainOnSharedContent ||
-    navigation::NAV == CurrentNavigation::ScSearchSubmenuBottom ||
-    navigation::NAV == CurrentNavigation::ScHoverReplay
-    {
-        key_to_press = key_a;
-        nn::oe::ReportUserIsActive(); // prevent switch from dimming
-    } else if navigation::NAV == CurrentNavigation::ScKeyboard {
-        key_to_press = key_a; // press a and start to start typing keys and enter when possible
-        key_to_alternate = key_start;
-    } else if navigation::NAV == CurrentNavigation::ScWaitingForGame {
-        key_to_hold = key_x;
-    } else if navigation::NAV == CurrentNavigation::ScGO {
-        if !navigation::should_wait() {
-            key_to_hold = key_x | key_down; // press x+down to hide ui
-            println!("input down to hide UI");
-            navigation::NAV = CurrentNavigation::ScPlayback
+// Press and release `buttons` over and over.
+fn tap(buttons: u64) -> Schedule {
+    Schedule::repeating(vec![
+        scheduler::press(buttons, TAP_PRESS),
+        scheduler::release(TAP_RELEASE),
+    ])
+}
+
+fn schedule_for(nav: CurrentNavigation) -> Schedule {
+    match nav {
+        CurrentNavigation::ScVideo
+        | CurrentNavigation::ScIncompatible
+        | CurrentNavigation::ScNoResult
+        | CurrentNavigation::RecoverToSubmenu
+        | CurrentNavigation::RecoverToMain
+        | CurrentNavigation::DaemonBackingOut
+        | CurrentNavigation::ScPlayback
+        | CurrentNavigation::DoneHoverPlay => tap(KEY_B),
+        CurrentNavigation::MainOnMelee | CurrentNavigation::MainInOnline => tap(KEY_DOWN),
+        CurrentNavigation::MainOnSpirits => tap(KEY_RIGHT),
+        CurrentNavigation::ScWaitingForLoad => tap(KEY_X),
+        CurrentNavigation::MainOnOnline
+        | CurrentNavigation::MainOnSharedContent
+        | CurrentNavigation::ScSearchSubmenuBottom
+        | CurrentNavigation::ScHoverReplay => tap(KEY_A),
+        // press a and start to start typing keys and enter when possible
+        CurrentNavigation::ScKeyboard => Schedule::repeating(vec![
+            scheduler::press(KEY_A, TAP_PRESS),
+            scheduler::release(TAP_RELEASE),
+            scheduler::press(KEY_START, TAP_PRESS),
+            scheduler::release(TAP_RELEASE),
+        ]),
+        CurrentNavigation::ScWaitingForGame => Schedule::repeating(vec![
+            scheduler::press(KEY_X, Duration::Forever),
+        ]),
+        // press x+down to hide ui, once the game has settled
+        CurrentNavigation::ScGO => Schedule::once(vec![
+            scheduler::release(SETTLE_DELAY),
+            scheduler::press(KEY_X | KEY_DOWN, TAP_PRESS),
+        ]),
+        // press up once to wrap around to "Enter ID" at the bottom
+        CurrentNavigation::ScSearchSubmenuTop => Schedule::once(vec![
+            scheduler::release(SETTLE_DELAY),
+            scheduler::press(KEY_UP, TAP_PRESS),
+            scheduler::release(TAP_RELEASE),
+        ]),
+        _ => Schedule::idle(),
+    }
+}
+
+// Called when a non-repeating schedule has finished all of its steps.
+unsafe fn on_schedule_done(nav: CurrentNavigation) {
+    match nav {
+        CurrentNavigation::ScGO => {
+            println!("[input] Hid UI, watching playback");
+            navigation::set_nav(CurrentNavigation::ScPlayback);
         }
+        CurrentNavigation::ScSearchSubmenuTop => {
+            println!("[input] Input up, on Enter ID");
+            navigation::set_nav(CurrentNavigation::ScSearchSubmenuBottom);
+        }
+        _ => {}
     }
+}
+
+unsafe fn handle_menu_navigate(state: *mut NpadHandheldState) {
+    let nav = navigation::NAV;
 
-    let mut rng = rand::thread_rng();
-    // Need to space apart presses so it does not seem like we are holding the button.
-    let n: u32 = rng.gen_range(0..3);
-    if n == 1 {
-        (*state).Buttons |= key_to_press;
-    } else {
-        (*state).Buttons |= key_to_alternate;
+    match nav {
+        CurrentNavigation::ScPlayback
+        | CurrentNavigation::DoneHoverPlay
+        | CurrentNavigation::MainOnOnline
+        | CurrentNavigation::MainOnSharedContent
+        | CurrentNavigation::ScSearchSubmenuBottom
+        | CurrentNavigation::ScHoverReplay => nn::oe::ReportUserIsActive(), // prevent switch from dimming
+        _ => {}
     }
-    (*state).Buttons |= key_to_hold;
-
-    if navigation::NAV == CurrentNavigation::ScSearchSubmenuTop {
-        if !navigation::should_wait() {
-            (*state).Buttons |= key_up;
-            println!("Input up, delay reached!");
-            navigation::NAV = CurrentNavigation::ScSearchSubmenuBottom;
-        } 
+
+    // Hold off on opening the keyboard while we're backing off from a failed replay.
+    if nav == CurrentNavigation::ScSearchSubmenuBottom && playaid::in_backoff() {
+        SCHEDULE = None;
+        trace::LAST_BUTTONS = (*state).Buttons;
+        return;
     }
+
+    let needs_new_schedule = match &SCHEDULE {
+        Some((schedule_nav, _)) => *schedule_nav != nav,
+        None => true,
+    };
+    if needs_new_schedule {
+        SCHEDULE = Some((nav, schedule_for(nav)));
+    }
+
+    let buttons = match &SCHEDULE {
+        Some((_, schedule)) => schedule.buttons_now(),
+        None => None,
+    };
+    match buttons {
+        Some(buttons) => (*state).Buttons |= buttons,
+        None => on_schedule_done(nav),
+    }
+
+    trace::LAST_BUTTONS = (*state).Buttons;
 }
 
 #[allow(improper_ctypes)]
 extern "C" {
     pub fn add_nn_hid_hook(callback: fn(*mut NpadHandheldState,*const u32));
-}
\ No newline at end of file
+}
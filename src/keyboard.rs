@@ -1,22 +1,127 @@
-use skyline::libc::memcpy;
+use serde_json::json;
 
 use crate::navigation::{self, CurrentNavigation};
+use crate::outcome;
 use crate::playaid;
+use crate::replay_id::REPLAY_ID_LEN;
 
 static KEYBOARD_OFFSET: usize = 0x39c5380;
 
+// Set once we've typed an ID into the search prompt, cleared once navigation confirms the search went through.
+static mut SEARCH_PENDING: bool = false;
+
+// How many prompts in a row can fail the check below before we stop trying to search at all.
+const MAX_MISMATCHED_PROMPTS: u32 = 3;
+static mut MISMATCHED_PROMPTS: u32 = 0;
+
+// What kind of software keyboard prompt the game is asking for.
+#[derive(PartialEq, Debug)]
+enum KeyboardPrompt {
+    ReplaySearch, // "Enter ID" on the Shared Content search menu
+    Mismatched { keyboard_mode: u32, text_max_length: i32 }, // Opened by us from the search menu, but not "Enter ID"
+    Unrelated, // Anything else (naming, online search, ...), which we leave to the game
+}
+
+// ShowKeyboardArg starts with the nn::swkbd::KeyboardConfig the game fills in for the prompt: keyboardMode is its
+// first field, and textMaxLength comes right after guideText[257], at 0x3AC.
+const KEYBOARD_MODE_OFFSET: usize = 0x0;
+const TEXT_MAX_LENGTH_OFFSET: usize = 0x3ac;
+// nn::swkbd::KeyboardMode_ASCII, which the "Enter ID" prompt uses to limit input to letters and digits.
+const KEYBOARD_MODE_ASCII: u32 = 2;
+
+unsafe fn classify_prompt(nav: CurrentNavigation, show_keyboard_arg: *const u64) -> KeyboardPrompt {
+    if show_keyboard_arg.is_null() {
+        return KeyboardPrompt::Unrelated;
+    }
+    // We only ever open the keyboard on purpose by pressing A on "Enter ID".
+    match nav {
+        CurrentNavigation::ScSearchSubmenuBottom | CurrentNavigation::ScKeyboard => {}
+        _ => return KeyboardPrompt::Unrelated,
+    }
+    // Something else can still open a prompt while we're there (a name entry after a disconnect, say), so check
+    // that it's asking for exactly one replay ID.
+    let config = show_keyboard_arg as *const u8;
+    let keyboard_mode = *(config.add(KEYBOARD_MODE_OFFSET) as *const u32);
+    let text_max_length = *(config.add(TEXT_MAX_LENGTH_OFFSET) as *const i32);
+    if keyboard_mode != KEYBOARD_MODE_ASCII || text_max_length != REPLAY_ID_LEN as i32 {
+        return KeyboardPrompt::Mismatched { keyboard_mode, text_max_length };
+    }
+    KeyboardPrompt::ReplaySearch
+}
+
+// We pressed A on "Enter ID" and got a prompt that doesn't look like it. Let the game show it, but back out with
+// B rather than tapping A and Start into it, and give up on searching if it keeps happening.
+unsafe fn on_mismatched_prompt(keyboard_mode: u32, text_max_length: i32) {
+    MISMATCHED_PROMPTS += 1;
+    println!(
+        "[keyboard] Prompt has mode {} and max length {}, not a replay search. Backing out!",
+        keyboard_mode, text_max_length
+    );
+    outcome::record(
+        json!({
+            "event": "keyboard_mismatch",
+            "state": format!("{:?}", navigation::NAV),
+            "keyboard_mode": keyboard_mode,
+            "text_max_length": text_max_length,
+            "mismatches": MISMATCHED_PROMPTS,
+            "replay_id": playaid::current_replay_id().map(|replay_id| replay_id.to_string()),
+        })
+    );
+    navigation::set_nav(CurrentNavigation::RecoverToSubmenu);
+    if MISMATCHED_PROMPTS >= MAX_MISMATCHED_PROMPTS {
+        println!("[keyboard] Search prompt keeps failing the check, giving up on the batch");
+        playaid::final_replay();
+    }
+}
+
 #[skyline::hook(offset = KEYBOARD_OFFSET)] 
-pub unsafe fn show_keyboard(string: *mut *mut u16, _show_keyboard_arg: *const u64) -> u32 {
+pub unsafe fn show_keyboard(string: *mut *mut u16, show_keyboard_arg: *const u64) -> u32 {
+    match classify_prompt(navigation::NAV, show_keyboard_arg) {
+        KeyboardPrompt::ReplaySearch => MISMATCHED_PROMPTS = 0,
+        KeyboardPrompt::Mismatched { keyboard_mode, text_max_length } => {
+            on_mismatched_prompt(keyboard_mode, text_max_length);
+            return call_original!(string, show_keyboard_arg);
+        }
+        KeyboardPrompt::Unrelated => {
+            println!("[keyboard] Leaving keyboard prompt alone in {:?}", navigation::NAV);
+            return call_original!(string, show_keyboard_arg);
+        }
+    }
+
     let return_code = 0;
-    println!("Adding into keyboard the id: {}", playaid::TEST_ID[playaid::ID_INDEX]);
-    let new_string_vec: Vec<u16> = playaid::TEST_ID[playaid::ID_INDEX].encode_utf16().chain(core::iter::once(0)).collect();
-    let raw_vec_info = new_string_vec.into_raw_parts();
-    memcpy(*string as _, raw_vec_info.0 as _, raw_vec_info.2 * 2);
-    increment_id_index();
-    navigation::NAV = CurrentNavigation::ScSearchResults;
+    if string.is_null() || (*string).is_null() {
+        println!("[keyboard] No keyboard buffer to type into!");
+        return return_code;
+    }
+
+    // IDs were checked when they were queued, so this is always safe to type.
+    let replay_id = match playaid::current_replay_id() {
+        Some(replay_id) => replay_id,
+        None => {
+            println!("[keyboard] No replay ids left to type!");
+            playaid::final_replay();
+            return return_code;
+        }
+    };
+
+    println!("Adding into keyboard the id: {}", replay_id);
+    // The ID is a fixed length, so this never writes more than REPLAY_ID_LEN characters plus the terminator.
+    let utf16 = replay_id.to_utf16();
+    std::ptr::copy_nonoverlapping(utf16.as_ptr(), *string, utf16.len());
+    SEARCH_PENDING = true;
+    navigation::set_nav(CurrentNavigation::ScSearchResults);
     return_code
 }
 
+// Called by navigation once the search we typed has come back (with results or a popup). Only then does it
+// count as an attempt, so a cancelled search just types the same ID again next time.
+pub unsafe fn confirm_search() {
+    if SEARCH_PENDING {
+        SEARCH_PENDING = false;
+        playaid::start_attempt();
+    }
+}
+
 pub fn init() {
     skyline::install_hooks!(
         show_keyboard
@@ -32,11 +137,3 @@ unsafe fn _utf16_to_string(char_ptr: *const u16) -> String {
     let rust_string = String::from_utf16_lossy(utf16_slice);
     rust_string
 }
-
-pub unsafe fn increment_id_index() {
-    playaid::ID_INDEX += 1;
-    if playaid::ID_INDEX >= playaid::TEST_ID.len() {
-        playaid::ID_INDEX = 0;
-        playaid::final_replay();
-    }
-}
\ No newline at end of file
@@ -6,10 +6,24 @@
 #![feature(vec_into_raw_parts)]
 use crate::navigation::CurrentNavigation;
 
+mod clock;
 mod navigation;
 mod input;
 mod keyboard;
 mod playaid;
+mod outcome;
+mod watchdog;
+mod trace;
+mod scheduler;
+// Shared with the host-side tools in ult_log.
+#[path = "../ult_log/src/names.rs"]
+mod names;
+#[path = "../ult_log/src/nav_state.rs"]
+mod nav_state;
+#[path = "../ult_log/src/queue.rs"]
+mod queue;
+#[path = "../ult_log/src/replay_id.rs"]
+mod replay_id;
 
 use skyline;
 use acmd::acmd;
@@ -22,7 +36,7 @@ use smash::app::{ utility, sv_system, smashball };
 use smash::hash40;
 use smash::lua2cpp::{ L2CFighterCommon, L2CFighterBase, L2CFighterBase_global_reset };
 use serde_json::json;
-use std::sync::atomic::{ AtomicU32, Ordering };
+use std::sync::atomic::{ AtomicBool, AtomicU32, Ordering };
 use std::sync::Mutex;
 use std::fs::File;
 use std::fs::OpenOptions;
@@ -33,6 +47,10 @@ use std::rc::Rc;
 use skyline::nn::{ time };
 use std::time::{ SystemTime, UNIX_EPOCH };
 
+// Also log names next to the raw fighter, stage, status and motion values. Off by default since it makes every
+// line longer, and the host tools can look them up anyway.
+const LOG_NAMES: bool = false;
+
 lazy_static! {
     static ref FILE_PATH: Mutex<String> = Mutex::new(String::new());
     static ref FIGHTER_LOG_COUNT: Mutex<usize> = Mutex::new(0);
@@ -66,6 +84,19 @@ extern "C" {
 
     #[link_name = "\u{1}_ZN3app17sv_camera_manager7get_fovEv"]
     pub fn get_camera_fov() -> f32;
+
+    // 0 when handheld, 1 when docked.
+    #[link_name = "\u{1}_ZN2nn2oe16GetOperationModeEv"]
+    pub fn get_operation_mode() -> i32;
+}
+
+// The resolution the game renders at, which depends on whether the Switch is docked.
+fn screen_size() -> (u32, u32) {
+    if unsafe { get_operation_mode() } == 1 {
+        (1920, 1080)
+    } else {
+        (1280, 720)
+    }
 }
 
 static mut FIGHTER_MANAGER_ADDR: usize = 0;
@@ -75,6 +106,10 @@ static mut FIGHTER_MANAGER_ADDR: usize = 0;
 // 2 - we have finished logging.
 static LOGGING_STATE: AtomicU32 = AtomicU32::new(0);
 
+// Whether this match's log has its sync event yet. Written on the first frame we log, which is the first frame
+// `is_ready_go` is true, so it lines up with GO! appearing on screen.
+static SYNC_LOGGED: AtomicBool = AtomicBool::new(false);
+
 // This gets called whenever a match starts or ends. Still gets called once per fighter which is odd.
 // A typical fight will have the following logs.
 //   HIT on_match_start_or_end
@@ -106,6 +141,7 @@ pub fn on_match_start_or_end(fighter: &mut L2CFighterBase) -> L2CValue {
         // We are in the starting state, it's time to create a log.
         println!("[ult-logger] Starting");
         LOGGING_STATE.store(1, Ordering::SeqCst);
+        SYNC_LOGGED.store(false, Ordering::SeqCst);
     }
 
     if is_result_mode && LOGGING_STATE.load(Ordering::SeqCst) == 1 {
@@ -127,16 +163,11 @@ pub fn on_match_start_or_end(fighter: &mut L2CFighterBase) -> L2CValue {
             .duration_since(UNIX_EPOCH)
             .expect("Time went backwards")
             .as_millis();
-        // *file_path = format!("sd:/fight-{}-vs-{}-{}.txt", fighter1, fighter2, event_time);
-
-        let mut replay_id = "XXXXXXXX";
-        unsafe {
-            // This is index - 1 because we increment right after we input info into the keyboard.
-            // This is sloppy code.
-            replay_id = playaid::TEST_ID[playaid::ID_INDEX - 1];
-        }
-
-        *file_path = format!("sd:/{}-{}.txt", replay_id, event_time);
+        // Name the log after the ID we typed into the keyboard, or the fighters for a match we didn't queue.
+        *file_path = match playaid::current_replay_id() {
+            Some(replay_id) => format!("sd:/{}-{}.txt", replay_id, event_time),
+            None => format!("sd:/fight-{}-vs-{}-{}.txt", fighter1, fighter2, event_time),
+        };
         File::create(&*file_path);
 
         let file = OpenOptions::new().write(true).append(true).open(&*file_path);
@@ -151,6 +182,7 @@ pub fn on_match_start_or_end(fighter: &mut L2CFighterBase) -> L2CValue {
         }
 
         println!("[ult-logger] Wrote to {}", file_path.to_string());
+        playaid::mark_outcome(playaid::ReplayOutcome::Recorded);
         // Clear the buffer after writing
         buffer.clear();
     }
@@ -252,7 +284,7 @@ pub fn once_per_frame_per_fighter(fighter: &mut L2CFighterCommon) {
             *fighter2 = format!("{}", fighter_name);
         }
 
-        let json_log =
+        let mut json_log =
             json!({
             "num_frames_left": num_frames_left,
             "fighter_id": fighter_id,
@@ -283,10 +315,65 @@ pub fn once_per_frame_per_fighter(fighter: &mut L2CFighterCommon) {
             "stage_id": stage_id,
         });
 
+        if LOG_NAMES {
+            json_log["fighter"] = json!(names::fighter_name(fighter_name));
+            json_log["status"] = json!(names::status_name(fighter_name, fighter_status_kind));
+            json_log["motion"] = json!(names::motion_name(fighter_motion_kind));
+            json_log["stage"] = json!(names::stage_name(stage_id));
+        }
+
         let PUSH_TO_BUFFER = true;
         if PUSH_TO_BUFFER {
             buffer.push_str(&format!("{}\n", json_log));
         }
+
+        // Once per frame, with the first fighter: when the frame happened, the sync marker if this is the first
+        // frame, and the camera.
+        if PUSH_TO_BUFFER && fighter_id == 0 {
+            let timestamp_us = clock::monotonic_us();
+            buffer.push_str(&format!("{}\n", json!({ "event": "frame_time", "timestamp_us": timestamp_us })));
+
+            if !SYNC_LOGGED.swap(true, Ordering::SeqCst) {
+                let sync_log =
+                    json!({
+                    "event": "sync",
+                    "marker": "ready_go",
+                    "timestamp_us": timestamp_us,
+                    "wall_clock_ms": SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|duration| duration.as_millis() as u64)
+                        .unwrap_or(0),
+                });
+                buffer.push_str(&format!("{}\n", sync_log));
+            }
+
+            // The full camera block. The camera manager doesn't expose an up vector or roll; the battle camera
+            // never rolls, so world up is up.
+            let (screen_width, screen_height) = screen_size();
+            let camera_log =
+                json!({
+                "event": "camera",
+                "position": {
+                    "x": cam_pos.x,
+                    "y": cam_pos.y,
+                    "z": cam_pos.z,
+                },
+                "internal_position": {
+                    "x": internal_cam_pos.x,
+                    "y": internal_cam_pos.y,
+                    "z": internal_cam_pos.z,
+                },
+                "target_position": {
+                    "x": cam_target.x,
+                    "y": cam_target.y,
+                    "z": cam_target.z,
+                },
+                "fov": cam_fov,
+                "screen_width": screen_width,
+                "screen_height": screen_height,
+            });
+            buffer.push_str(&format!("{}\n", camera_log));
+        }
     }
 }
 
@@ -321,7 +408,7 @@ fn change_scene_sequence(
     {
         println!("[ult-logger] Made it to Shared Content!");
         unsafe {
-            navigation::NAV = CurrentNavigation::ScWaitingForLoad;
+            navigation::set_nav(CurrentNavigation::ScWaitingForLoad);
         }
     }
     call_original!(queue, fnv1, fnv2, parameters);
@@ -403,6 +490,13 @@ pub fn main() {
     // Add panic hook
     hook_panic();
 
+    // Open this batch's outcome file before navigation starts recording into it
+    outcome::init();
+    trace::init();
+
+    // Queue up any replay IDs listed on the SD card
+    playaid::load_manifest();
+
     // Initialize hooks for navigation and keyboard
     navigation::init();
     keyboard::init();
@@ -0,0 +1,52 @@
+use lazy_static::lazy_static;
+use serde_json::{ json, Value };
+use skyline::nn::{ time };
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+lazy_static! {
+    static ref OUTCOME_PATH: Mutex<String> = Mutex::new(String::new());
+}
+
+// Shared by every per-batch file so they can be matched up afterwards.
+pub static mut BATCH_STARTED_AT: u128 = 0;
+
+// Wall-clock milliseconds since the epoch, for file names and timestamps. Durations use `clock::monotonic_ms`.
+pub fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis()
+}
+
+// Every boot of the plugin is one batch, and gets its own outcome file so an overnight run can be reviewed
+// the next morning.
+pub fn init() {
+    unsafe {
+        time::Initialize();
+        BATCH_STARTED_AT = now_ms();
+    }
+    let mut outcome_path = OUTCOME_PATH.lock().unwrap();
+    *outcome_path = format!("sd:/batch-{}-outcomes.txt", unsafe { BATCH_STARTED_AT });
+    println!("[outcome] Writing batch outcomes to {}", outcome_path);
+}
+
+// Append a single JSON line to the batch outcome file. We never panic here, losing an outcome line is better
+// than halting the batch.
+pub fn record(mut entry: Value) {
+    entry["timestamp"] = json!(now_ms());
+
+    let outcome_path = OUTCOME_PATH.lock().unwrap();
+    let file = OpenOptions::new().create(true).append(true).open(&*outcome_path);
+
+    match file {
+        Err(e) => println!("[outcome] Couldn't open {}: {}", outcome_path, e),
+        Ok(mut file) => {
+            if let Err(e) = writeln!(file, "{}", entry) {
+                println!("[outcome] Couldn't write to {}: {}", outcome_path, e);
+            }
+        }
+    }
+}
@@ -1,30 +1,220 @@
+use lazy_static::lazy_static;
+use serde_json::json;
+use std::fs;
 use std::process;
+use std::sync::Mutex;
+
+use crate::clock;
+use crate::navigation::{self, CurrentNavigation};
+use crate::outcome;
+use crate::queue::ReplayQueue;
+pub use crate::queue::ReplayOutcome;
+use crate::replay_id::ReplayId;
+use crate::trace;
 
 // Statics for Replay IDs, populate these however from your sheets
 pub static mut TEST_ID: &'static [&'static str] = &[
 
 ];
 
-pub static mut ID_INDEX: usize = 0;
+// Replay IDs can also be listed in this file on the SD card, one per line. They're queued after TEST_ID.
+pub const MANIFEST_PATH: &str = "sd:/replay-queue.txt";
+
+// In daemon mode we don't exit when the queue runs out. Instead we wait on the main menu and re-read the manifest
+// every MANIFEST_POLL_MS for newly appended IDs.
+pub const DAEMON_MODE: bool = false;
+pub const MANIFEST_POLL_MS: u128 = 60_000;
+
+// How many times we'll try a replay that fails to load or record before giving up on it.
+pub const MAX_ATTEMPTS: u32 = 3;
+// How long to wait before retrying, doubled after each failed attempt.
+pub const RETRY_BACKOFF_MS: u128 = 5_000;
+pub const MAX_RETRY_BACKOFF_MS: u128 = 20_000;
+
+lazy_static! {
+    // The queue of replay IDs from TEST_ID and the manifest, with the outcome and number of attempts for each.
+    pub static ref QUEUE: Mutex<ReplayQueue> = Mutex::new(
+        ReplayQueue::new(parse_ids(unsafe { TEST_ID }.iter().copied()))
+    );
+}
+
+// How many IDs from TEST_ID and the manifest weren't well-formed, and so were never queued.
+static mut REJECTED_IDS: usize = 0;
+
+// Parse IDs before they're queued. Anything that isn't a valid ID is recorded as invalid straight away rather
+// than taking a turn in the queue.
+fn parse_ids<'a>(raw_ids: impl Iterator<Item = &'a str>) -> Vec<ReplayId> {
+    raw_ids
+        .filter_map(|raw_id| match ReplayId::parse(raw_id) {
+            Ok(replay_id) => Some(replay_id),
+            Err(e) => {
+                println!("Rejected replay id {:?}: {}", raw_id, e);
+                outcome::record(
+                    json!({
+                        "event": "replay_outcome",
+                        "replay_id": raw_id,
+                        "outcome": ReplayOutcome::InvalidId.name(),
+                        "attempts": 0,
+                    })
+                );
+                unsafe {
+                    REJECTED_IDS += 1;
+                }
+                None
+            }
+        })
+        .collect()
+}
+
+// How many manifest lines we've already read, since the manifest is only ever appended to.
+static mut MANIFEST_LINES_READ: usize = 0;
+static mut NEXT_MANIFEST_POLL: u128 = 0;
+
+fn read_manifest() -> Vec<String> {
+    match fs::read_to_string(MANIFEST_PATH) {
+        Err(_) => vec![],
+        Ok(contents) => contents
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.to_string())
+            .collect(),
+    }
+}
+
+// Queue any IDs that have been added to the manifest since we last read it. Returns how many were added.
+pub fn load_manifest() -> usize {
+    let manifest_lines = read_manifest();
+    let new_lines = &manifest_lines[unsafe { MANIFEST_LINES_READ }.min(manifest_lines.len())..];
+    unsafe {
+        MANIFEST_LINES_READ += new_lines.len();
+    }
+    let new_ids = parse_ids(new_lines.iter().map(|line| line.as_str()));
+    let added = new_ids.len();
+    if added > 0 {
+        println!("Queued {} replay ids from {}", added, MANIFEST_PATH);
+        QUEUE.lock().unwrap().append(new_ids);
+    }
+    added
+}
+
+// Called on every input poll in daemon mode while we wait for more IDs.
+pub unsafe fn poll_manifest() {
+    if navigation::NAV != CurrentNavigation::DaemonWaiting || clock::monotonic_ms() < NEXT_MANIFEST_POLL {
+        return;
+    }
+    NEXT_MANIFEST_POLL = clock::monotonic_ms() + MANIFEST_POLL_MS;
+    if load_manifest() > 0 {
+        navigation::resume_from_daemon();
+    }
+}
+
+// Don't type the next ID until this time, so a flaky connection gets a moment to recover.
+static mut RETRY_NOT_BEFORE: u128 = 0;
+
+// The ID we're currently searching for or watching, if there are any left.
+pub fn current_replay_id() -> Option<ReplayId> {
+    QUEUE.lock().unwrap().current()
+}
 
 // Functions that may be useful to implement
 
-// Called when the last ID (ID_INDEX - 1) was an invalid replay
-pub fn handle_bad_id() {
+// Called once we know what the current ID pointed at. Recorded in the queue and the batch outcome file, then we
+// move on to the next ID.
+pub fn mark_outcome(replay_outcome: ReplayOutcome) {
+    let mut queue = QUEUE.lock().unwrap();
+    let replay_id = match queue.current() {
+        Some(replay_id) => replay_id,
+        None => return,
+    };
+    println!("Replay id {} was {}", replay_id, replay_outcome.name());
+
+    outcome::record(
+        json!({
+            "event": "replay_outcome",
+            "replay_id": replay_id.as_str(),
+            "outcome": replay_outcome.name(),
+            "attempts": queue.attempts(),
+        })
+    );
+    queue.mark_outcome(replay_outcome);
+
+    if queue.is_finished() {
+        let (recorded, total) = queue.tally();
+        outcome::record(
+            json!({
+                "event": "batch_complete",
+                "recorded": recorded,
+                "total": total,
+                "rejected": unsafe { REJECTED_IDS },
+            })
+        );
+        final_replay();
+    }
+}
+
+// Called when a search for the current ID goes through.
+pub fn start_attempt() {
+    QUEUE.lock().unwrap().start_attempt();
+}
+
+// Called when the current ID failed to load or record. Either leaves it at the front of the queue so it's
+// searched again after a backoff, or gives up on it once it's used all of its attempts.
+pub fn replay_failed(reason: &str) {
+    let mut queue = QUEUE.lock().unwrap();
+    if !queue.in_flight() {
+        return;
+    }
+
+    let attempts = queue.attempts();
+    if attempts >= MAX_ATTEMPTS {
+        drop(queue);
+        mark_outcome(ReplayOutcome::FailedAfterRetries(attempts.saturating_sub(1)));
+        return;
+    }
+
+    let backoff = (RETRY_BACKOFF_MS << attempts.saturating_sub(1)).min(MAX_RETRY_BACKOFF_MS);
+    let replay_id = queue.current().map(|replay_id| replay_id.to_string());
+    println!("Replay id {:?} failed ({}), retrying in {}ms", replay_id, reason, backoff);
+    outcome::record(
+        json!({
+            "event": "replay_retry",
+            "replay_id": replay_id,
+            "reason": reason,
+            "attempts": attempts,
+            "backoff_ms": backoff,
+        })
+    );
+    queue.abandon_attempt();
     unsafe {
-        println!("Hit a bad replay id: {}", TEST_ID[ID_INDEX - 1]);
+        RETRY_NOT_BEFORE = clock::monotonic_ms() + backoff;
     }
 }
 
+// Whether we're still waiting out a retry backoff.
+pub fn in_backoff() -> bool {
+    clock::monotonic_ms() < unsafe { RETRY_NOT_BEFORE }
+}
+
 // Called when a replay is finished and we've returned to the ID selection
 pub fn replay_done() {
-    unsafe {
-        println!("Completed the replay id: {}", TEST_ID[ID_INDEX - 1]);
+    if QUEUE.lock().unwrap().in_flight() {
+        // We made it back to the menu without writing a log, so playback was cut short.
+        replay_failed("aborted");
     }
 }
 
-// Called when we're about to watch the last replay in the array
+// Called once the last ID in the queue has an outcome (after its log has been written, if it was a replay)
 pub fn final_replay() {
     println!("We have played all of the replays");
+    if DAEMON_MODE {
+        println!("Waiting on the main menu for more replay ids");
+        unsafe {
+            NEXT_MANIFEST_POLL = clock::monotonic_ms() + MANIFEST_POLL_MS;
+            navigation::set_nav(CurrentNavigation::DaemonBackingOut);
+        }
+        return;
+    }
+    trace::flush();
     process::exit(0);
-}
\ No newline at end of file
+}
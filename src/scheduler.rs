@@ -0,0 +1,86 @@
+use crate::clock;
+
+// The game runs at 60fps, so frame durations are converted to real time at that rate. The npad hook is polled
+// more than once a frame, so we can't just count polls.
+const MS_PER_FRAME: f64 = 1000.0 / 60.0;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Duration {
+    Ms(u64),
+    Frames(u64),
+    // Stay on this step until navigation moves us to another state.
+    Forever,
+}
+
+impl Duration {
+    fn as_ms(&self) -> Option<u128> {
+        match self {
+            Duration::Ms(ms) => Some(*ms as u128),
+            Duration::Frames(frames) => Some((*frames as f64 * MS_PER_FRAME).round() as u128),
+            Duration::Forever => None,
+        }
+    }
+}
+
+// Hold `buttons` (0 for released) for `duration`.
+#[derive(Clone, Copy, Debug)]
+pub struct Step {
+    pub buttons: u64,
+    pub duration: Duration,
+}
+
+pub fn press(buttons: u64, duration: Duration) -> Step {
+    Step { buttons, duration }
+}
+
+pub fn release(duration: Duration) -> Step {
+    Step { buttons: 0, duration }
+}
+
+#[derive(Clone, Debug)]
+pub struct Schedule {
+    steps: Vec<Step>,
+    // Start back at the first step once the last one finishes, otherwise the schedule is done.
+    repeat: bool,
+    started_at: u128,
+}
+
+impl Schedule {
+    pub fn once(steps: Vec<Step>) -> Schedule {
+        Schedule { steps, repeat: false, started_at: clock::monotonic_ms() }
+    }
+
+    pub fn repeating(steps: Vec<Step>) -> Schedule {
+        Schedule { steps, repeat: true, started_at: clock::monotonic_ms() }
+    }
+
+    pub fn idle() -> Schedule {
+        Schedule::repeating(vec![release(Duration::Forever)])
+    }
+
+    // The buttons to inject right now, or None once a non-repeating schedule has run out of steps.
+    pub fn buttons_now(&self) -> Option<u64> {
+        self.buttons_at(clock::monotonic_ms().saturating_sub(self.started_at))
+    }
+
+    pub fn buttons_at(&self, elapsed_ms: u128) -> Option<u64> {
+        let mut offset = elapsed_ms;
+
+        // A repeating schedule wraps around, unless it has a Forever step which it will never get past.
+        let total_ms: Option<u128> = self.steps.iter().map(|step| step.duration.as_ms()).sum();
+        if let Some(total_ms) = total_ms {
+            if self.repeat && total_ms > 0 {
+                offset %= total_ms;
+            }
+        }
+
+        for step in &self.steps {
+            match step.duration.as_ms() {
+                None => return Some(step.buttons),
+                Some(ms) if offset < ms => return Some(step.buttons),
+                Some(ms) => offset -= ms,
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,78 @@
+use lazy_static::lazy_static;
+use serde_json::json;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::Mutex;
+
+use crate::navigation::CurrentNavigation;
+use crate::outcome;
+
+// Flip this on to record every MBST label we see to the SD card. The trace can be parsed on the host with
+// `ult_log::trace` to see what navigation did during a failing run.
+pub const TRACE_ENABLED: bool = false;
+
+// Entries are held in memory and written out when navigation changes state, since the game fetches labels many
+// times a frame. Also write them out once this many pile up in one state.
+const MAX_BUFFERED_ENTRIES: usize = 256;
+
+lazy_static! {
+    static ref TRACE_PATH: Mutex<String> = Mutex::new(String::new());
+    static ref TRACE_BUFFER: Mutex<Vec<String>> = Mutex::new(Vec::new());
+}
+
+// The buttons we most recently injected in the npad hook.
+pub static mut LAST_BUTTONS: u64 = 0;
+
+pub fn init() {
+    if !TRACE_ENABLED {
+        return;
+    }
+    let mut trace_path = TRACE_PATH.lock().unwrap();
+    *trace_path = format!("sd:/batch-{}-trace.txt", unsafe { outcome::BATCH_STARTED_AT });
+    println!("[trace] Writing label trace to {}", trace_path);
+}
+
+pub fn record_label(label: &str, nav: CurrentNavigation, nav_after: CurrentNavigation) {
+    if !TRACE_ENABLED {
+        return;
+    }
+
+    let entry = json!({
+        "timestamp": outcome::now_ms(),
+        "label": label,
+        "nav": format!("{:?}", nav),
+        "nav_after": format!("{:?}", nav_after),
+        "buttons": unsafe { LAST_BUTTONS },
+    });
+
+    let mut buffer = TRACE_BUFFER.lock().unwrap();
+    buffer.push(entry.to_string());
+    if nav != nav_after || buffer.len() >= MAX_BUFFERED_ENTRIES {
+        write_out(&mut buffer);
+    }
+}
+
+// Write out anything still buffered, e.g. before exiting.
+pub fn flush() {
+    if TRACE_ENABLED {
+        write_out(&mut TRACE_BUFFER.lock().unwrap());
+    }
+}
+
+fn write_out(buffer: &mut Vec<String>) {
+    if buffer.is_empty() {
+        return;
+    }
+    let trace_path = TRACE_PATH.lock().unwrap();
+    let file = OpenOptions::new().create(true).append(true).open(&*trace_path);
+
+    match file {
+        Err(e) => println!("[trace] Couldn't open {}: {}", trace_path, e),
+        Ok(mut file) => {
+            if let Err(e) = writeln!(file, "{}", buffer.join("\n")) {
+                println!("[trace] Couldn't write to {}: {}", trace_path, e);
+            }
+        }
+    }
+    buffer.clear();
+}
@@ -0,0 +1,122 @@
+use serde_json::json;
+
+use crate::clock;
+use crate::navigation::{self, CurrentNavigation};
+use crate::outcome;
+use crate::playaid;
+
+// How many times in a row RecoverToMain can time out before we stop pressing B and wait for the main menu to
+// load. If that times out too, the watchdog gives up until navigation picks back up on its own.
+const MAX_RECOVERY_ATTEMPTS: u32 = 3;
+static mut RECOVERY_ATTEMPTS: u32 = 0;
+static mut GAVE_UP: bool = false;
+
+// How long we're willing to sit in a navigation state before assuming an expected label is never coming.
+fn timeout_ms(nav: CurrentNavigation) -> Option<u128> {
+    match nav {
+        CurrentNavigation::Complete | CurrentNavigation::DaemonWaiting => None,
+        // Booting into the main menu and connecting to online can both be slow.
+        CurrentNavigation::MainWaitingForLoad => Some(120_000),
+        CurrentNavigation::MainWaitingOnline | CurrentNavigation::ScWaitingForLoad => Some(60_000),
+        CurrentNavigation::ScWaitingForGame => Some(90_000),
+        // A replay can run for the full match timer plus the results screen.
+        CurrentNavigation::ScGO | CurrentNavigation::ScPlayback => Some(15 * 60_000),
+        _ => Some(30_000),
+    }
+}
+
+// Where to back out to when a state times out. Anything inside the search flow first tries to get back to the
+// search submenu; everything else goes back to the main menu. A stuck RecoverToMain is handled in `check`.
+fn recovery_for(nav: CurrentNavigation) -> CurrentNavigation {
+    match nav {
+        CurrentNavigation::ScSearchSubmenuTop
+        | CurrentNavigation::ScSearchSubmenuBottom
+        | CurrentNavigation::ScKeyboard
+        | CurrentNavigation::ScSearchResults
+        | CurrentNavigation::_ScBadId
+        | CurrentNavigation::ScHoverReplay
+        | CurrentNavigation::ScWaitingForGame
+        | CurrentNavigation::ScGO
+        | CurrentNavigation::ScPlayback
+        | CurrentNavigation::DoneHoverPlay
+        | CurrentNavigation::ScIncompatible
+        | CurrentNavigation::ScNoResult
+        | CurrentNavigation::ScVideo => CurrentNavigation::RecoverToSubmenu,
+        _ => CurrentNavigation::RecoverToMain,
+    }
+}
+
+// States where a stall means the current replay failed to load or record.
+fn is_loading_or_playing(nav: CurrentNavigation) -> bool {
+    match nav {
+        CurrentNavigation::ScHoverReplay
+        | CurrentNavigation::ScWaitingForGame
+        | CurrentNavigation::ScGO
+        | CurrentNavigation::ScPlayback => true,
+        _ => false,
+    }
+}
+
+// Called on every input poll. If we've been in the current state for too long, log the incident and kick
+// navigation into a recovery state.
+pub unsafe fn check() {
+    let nav = navigation::NAV;
+    // Any state other than backing out or waiting on the main menu means the last recovery worked.
+    if nav != CurrentNavigation::RecoverToMain && nav != CurrentNavigation::MainWaitingForLoad {
+        RECOVERY_ATTEMPTS = 0;
+        GAVE_UP = false;
+    }
+    if GAVE_UP {
+        return;
+    }
+    let timeout = match timeout_ms(nav) {
+        Some(timeout) => timeout,
+        None => return,
+    };
+
+    let waited = clock::monotonic_ms().saturating_sub(navigation::NAV_ENTERED_AT);
+    if waited < timeout {
+        return;
+    }
+
+    if nav == CurrentNavigation::MainWaitingForLoad && RECOVERY_ATTEMPTS >= MAX_RECOVERY_ATTEMPTS {
+        println!("[watchdog] Still stuck after {} recoveries, giving up", RECOVERY_ATTEMPTS);
+        outcome::record(
+            json!({
+                "event": "watchdog_gave_up",
+                "state": format!("{:?}", nav),
+                "recovery_attempts": RECOVERY_ATTEMPTS,
+                "replay_id": playaid::current_replay_id().map(|replay_id| replay_id.to_string()),
+            })
+        );
+        GAVE_UP = true;
+        return;
+    }
+
+    let recovery = if nav == CurrentNavigation::RecoverToMain {
+        RECOVERY_ATTEMPTS += 1;
+        if RECOVERY_ATTEMPTS >= MAX_RECOVERY_ATTEMPTS {
+            CurrentNavigation::MainWaitingForLoad
+        } else {
+            CurrentNavigation::RecoverToMain
+        }
+    } else {
+        recovery_for(nav)
+    };
+    println!("[watchdog] Stuck in {:?} for {}ms, recovering with {:?}", nav, waited, recovery);
+    outcome::record(
+        json!({
+            "event": "watchdog_timeout",
+            "state": format!("{:?}", nav),
+            "waited_ms": waited,
+            "recovery": format!("{:?}", recovery),
+            "replay_id": playaid::current_replay_id().map(|replay_id| replay_id.to_string()),
+        })
+    );
+    if is_loading_or_playing(nav) {
+        playaid::replay_failed("watchdog_timeout");
+    }
+    navigation::set_nav(recovery);
+    // set_nav leaves the timer alone when retrying the same state, so restart it here.
+    navigation::NAV_ENTERED_AT = clock::monotonic_ms();
+}
@@ -0,0 +1,590 @@
+// Inspect recorded matches offline.
+//
+//   ult-log summary [--json] [--names FILE] [--labels FILE] <log>...
+//   ult-log validate <log>...
+//   ult-log convert [--format parquet|arrow] [--events] [--out-dir DIR] <log>...
+//   ult-log csv [--wide] [--fields a,b,...] [--out-dir DIR] <log>...
+//   ult-log import --db PATH [--force] <log or directory>...
+//   ult-log stats [--names FILE] [--labels FILE] [--out-dir DIR] <log>...
+//   ult-log conversions [--json] [--reset-frames N] [--names FILE] [--labels FILE] <log>...
+//   ult-log framedata [--json] [--names FILE] [--labels FILE] <log or directory>...
+//   ult-log techniques [--json] [--rules FILE] [--names FILE] <log>...
+//   ult-log positions [--json] [--stages FILE] <log>...
+//   ult-log bboxes [--resolution WxH] [--box WxH] [--internal-camera] [--out-dir DIR] <log>...
+//   ult-log sync --video-times FILE --sync-at SECONDS [--out-dir DIR] <log>...
+//   ult-log hash [--labels FILE] <name or 0xhash>...
+
+use std::collections::HashMap;
+use std::env;
+use std::fs::{ self, File };
+use std::io::{ BufWriter, Write };
+use std::path::{ Path, PathBuf };
+use std::process;
+
+use ult_log::camera::{ self, BoxSize, Eye, Resolution };
+use ult_log::columnar;
+use ult_log::conversions::{ self, ConversionOptions };
+use ult_log::csv_export::{ self, CsvOptions, Layout };
+use ult_log::framedata::FrameDataTable;
+use ult_log::hash40::{ self, Labels };
+use ult_log::name_tables::NameTables;
+use ult_log::sqlite;
+use ult_log::stage::{ self, Region, StageTable };
+use ult_log::stats::StatsReport;
+use ult_log::techniques;
+use ult_log::summary::MatchSummary;
+use ult_log::validate::validate;
+use ult_log::video_sync;
+use ult_log::Match;
+
+const USAGE: &str = "usage:
+  ult-log summary [--json] [--names FILE] [--labels FILE] <log>...
+  ult-log validate <log>...
+  ult-log convert [--format parquet|arrow] [--events] [--out-dir DIR] <log>...
+  ult-log csv [--wide] [--fields a,b,...] [--out-dir DIR] <log>...
+  ult-log import --db PATH [--force] <log or directory>...
+  ult-log stats [--names FILE] [--labels FILE] [--out-dir DIR] <log>...
+  ult-log conversions [--json] [--reset-frames N] [--names FILE] [--labels FILE] <log>...
+  ult-log framedata [--json] [--names FILE] [--labels FILE] <log or directory>...
+  ult-log techniques [--json] [--rules FILE] [--names FILE] <log>...
+  ult-log positions [--json] [--stages FILE] <log>...
+  ult-log bboxes [--resolution WxH] [--box WxH] [--internal-camera] [--out-dir DIR] <log>...
+  ult-log sync --video-times FILE --sync-at SECONDS [--out-dir DIR] <log>...
+  ult-log hash [--labels FILE] <name or 0xhash>...";
+
+// Flags that take a value, e.g. `--format parquet`. Every other flag is a switch.
+const VALUE_FLAGS: [&str; 13] = [
+    "--format",
+    "--out-dir",
+    "--fields",
+    "--db",
+    "--names",
+    "--labels",
+    "--reset-frames",
+    "--rules",
+    "--stages",
+    "--resolution",
+    "--box",
+    "--video-times",
+    "--sync-at",
+];
+
+struct Args {
+    flags: HashMap<String, String>,
+    paths: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Args {
+        let mut flags = HashMap::new();
+        let mut paths = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if VALUE_FLAGS.contains(&arg.as_str()) {
+                match args.next() {
+                    Some(value) => flags.insert(arg.clone(), value.clone()),
+                    None => usage(),
+                };
+            } else if arg.starts_with("--") {
+                flags.insert(arg.clone(), String::new());
+            } else {
+                paths.extend(expand_path(arg));
+            }
+        }
+        if paths.is_empty() {
+            usage();
+        }
+        Args { flags, paths }
+    }
+
+    fn has(&self, flag: &str) -> bool {
+        self.flags.contains_key(flag)
+    }
+
+    fn value(&self, flag: &str) -> Option<&str> {
+        self.flags.get(flag).map(|value| value.as_str())
+    }
+}
+
+// A directory stands for every match log in it. The plugin's batch outcome and trace files live alongside the
+// logs on the SD card, so those are left out.
+fn expand_path(path: &str) -> Vec<String> {
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return vec![path.to_string()],
+    };
+    let mut logs: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map(|extension| extension == "txt").unwrap_or(false))
+        .filter(|path| {
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+            !name.starts_with("batch-") && name != "replay-queue.txt"
+        })
+        .map(|path| path.display().to_string())
+        .collect();
+    logs.sort();
+    logs
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+fn print_summary(path: &str, summary: &MatchSummary) {
+    println!("{}", path);
+    println!("  replay id:  {}", summary.replay_id.as_deref().unwrap_or("unknown"));
+    match (&summary.stage, summary.stage_id) {
+        (Some(stage), _) => println!("  stage:      {}", stage),
+        (None, Some(stage_id)) => println!("  stage:      {}", stage_id),
+        (None, None) => println!("  stage:      unknown"),
+    }
+    println!("  duration:   {} frames", summary.duration_frames);
+    for fighter in &summary.fighters {
+        println!(
+            "  player {}:   fighter {}, lost {} of {} stocks, ended on {:.1}%",
+            fighter.fighter_id + 1,
+            fighter.fighter.clone().unwrap_or_else(|| fighter.fighter_name.to_string()),
+            fighter.stocks_lost,
+            fighter.starting_stocks,
+            fighter.final_damage
+        );
+    }
+    println!("  malformed:  {} lines{}", summary.malformed_lines, if summary.truncated { " (truncated)" } else { "" });
+}
+
+// The embedded Hash40 labels, extended by `--labels` if it was given. Labels that don't match their hash are
+// skipped with a warning.
+fn labels(args: &Args) -> Labels {
+    let mut labels = Labels::embedded();
+    if let Some(path) = args.value("--labels") {
+        match Labels::read(path) {
+            Ok(from_file) => labels.extend(from_file),
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                process::exit(2);
+            }
+        }
+    }
+    for mismatch in &labels.mismatches {
+        eprintln!("warning: {}", mismatch);
+    }
+    labels
+}
+
+// The embedded name tables, extended by `--names` and `--labels` if they were given.
+fn names(args: &Args) -> NameTables {
+    let names = match args.value("--names") {
+        None => NameTables::embedded(),
+        Some(path) => match NameTables::read(path) {
+            Ok(names) => names,
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                process::exit(2);
+            }
+        },
+    };
+    names.with_labels(labels(args))
+}
+
+fn stats(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let names = names(args);
+    for path in &args.paths {
+        let report = StatsReport::from_match(&read(path), &names);
+        let out_path = output_path(path, args.value("--out-dir"), ".stats.json");
+        serde_json::to_writer_pretty(BufWriter::new(File::create(&out_path)?), &report)?;
+        println!("{} -> {} ({} conversions)", path, out_path.display(), report.conversions.len());
+    }
+    Ok(())
+}
+
+fn print_conversions(args: &Args) {
+    let names = names(args);
+    let mut options = ConversionOptions::default();
+    if let Some(reset_frames) = args.value("--reset-frames") {
+        options.reset_frames = reset_frames.parse().unwrap_or_else(|_| usage());
+    }
+
+    for path in &args.paths {
+        let log = read(path);
+        let found = conversions::find_conversions(&log, &options);
+        if args.has("--json") {
+            println!("{}", serde_json::to_string(&found).unwrap());
+            continue;
+        }
+
+        let fighter = |fighter_id: i32| {
+            let fighter_name = log.header.fighters.iter().find(|info| info.fighter_id == fighter_id).map(|info| info.fighter_name);
+            match fighter_name.and_then(|fighter_name| names.fighter(fighter_name)) {
+                Some(name) => format!("p{} {}", fighter_id + 1, name),
+                None => format!("p{}", fighter_id + 1),
+            }
+        };
+        println!("{}", path);
+        for conversion in &found {
+            let moves: Vec<String> = conversion
+                .moves()
+                .into_iter()
+                .map(|motion_kind| names.motion(motion_kind).map(str::to_string).unwrap_or_else(|| format!("{:#x}", motion_kind)))
+                .collect();
+            println!(
+                "  {}-{}  {} on {}  {:.1}% -> {:.1}%  {} hits: {}{}",
+                conversion.start_frame,
+                conversion.end_frame,
+                fighter(conversion.attacker_id),
+                fighter(conversion.defender_id),
+                conversion.start_percent,
+                conversion.end_percent,
+                conversion.hits.len(),
+                moves.join(", "),
+                if conversion.killed { "  KO" } else { "" }
+            );
+        }
+    }
+}
+
+// Measures frame data across every log given, and prints one table for all of them.
+fn print_framedata(args: &Args) {
+    let names = names(args);
+    let mut table = FrameDataTable::new();
+    for path in &args.paths {
+        table.add_match(&read(path));
+    }
+    let rows = table.rows(&names);
+    if args.has("--json") {
+        println!("{}", serde_json::to_string(&rows).unwrap());
+        return;
+    }
+
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    println!("{:<14} {:<28} {:>7} {:>7} {:>9} {:>8}", "fighter", "move", "startup", "total", "on shield", "samples");
+    for row in rows {
+        println!(
+            "{:<14} {:<28} {:>7} {:>7} {:>9} {:>8}",
+            row.fighter.clone().unwrap_or_else(|| row.fighter_name.to_string()),
+            row.motion.clone().unwrap_or_else(|| format!("{:#x}", row.motion_kind)),
+            optional(row.startup.map(|startup| startup.to_string())),
+            optional(row.total_frames.map(|total_frames| total_frames.to_string())),
+            optional(row.on_shield.map(|on_shield| format!("{:+}", on_shield))),
+            row.samples
+        );
+    }
+}
+
+fn print_techniques(args: &Args) {
+    let names = names(args);
+    let patterns = match args.value("--rules") {
+        None => techniques::builtin_patterns(),
+        Some(path) => techniques::read_rules(path).unwrap_or_else(|e| {
+            eprintln!("{}: {}", path, e);
+            process::exit(2);
+        }),
+    };
+
+    for path in &args.paths {
+        let actions = techniques::find_actions(&read(path), &patterns, &names);
+        if args.has("--json") {
+            println!("{}", serde_json::to_string(&actions).unwrap());
+            continue;
+        }
+        println!("{}", path);
+        for action in actions {
+            println!("  {}-{}  p{}  {}", action.start_frame, action.end_frame, action.fighter_id + 1, action.label);
+        }
+    }
+}
+
+// Prints where each fighter is relative to the stage on every frame, as CSV unless `--json` is given.
+fn print_positions(args: &Args) {
+    let mut stages = StageTable::embedded();
+    if let Some(path) = args.value("--stages") {
+        if let Err(e) = stages.extend_from_file(path) {
+            eprintln!("{}: {}", path, e);
+            process::exit(2);
+        }
+    }
+
+    for path in &args.paths {
+        let log = read(path);
+        let positions = stage::stage_positions(&log, &stages);
+        if positions.is_empty() {
+            eprintln!("{}: no geometry for stage {:?}", path, log.header.stage_id);
+            continue;
+        }
+        if args.has("--json") {
+            println!("{}", serde_json::to_string(&positions).unwrap());
+            continue;
+        }
+        println!("frame,fighter_id,region,normalized_x,normalized_y,distance_to_ledge,distance_to_blast_zone");
+        for position in positions {
+            let region = match position.region {
+                Region::Onstage => "onstage".to_string(),
+                Region::OnPlatform(index) => format!("platform_{}", index),
+                Region::Offstage => "offstage".to_string(),
+            };
+            println!(
+                "{},{},{},{:.3},{:.3},{:.1},{:.1}",
+                position.frame,
+                position.fighter_id,
+                region,
+                position.normalized.x,
+                position.normalized.y,
+                position.distance_to_ledge,
+                position.distance_to_blast_zone
+            );
+        }
+    }
+}
+
+// Parses a `1920x1080` style size.
+fn parse_size(value: &str) -> Option<(f32, f32)> {
+    let (width, height) = value.split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+fn bboxes(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let resolution = args.value("--resolution").map(|value| {
+        let (width, height) = parse_size(value).unwrap_or_else(|| usage());
+        Resolution { width: width as u32, height: height as u32 }
+    });
+    let eye = if args.has("--internal-camera") { Eye::InternalPosition } else { Eye::Position };
+    let size = match args.value("--box") {
+        Some(value) => parse_size(value).map(|(width, height)| BoxSize { width, height }).unwrap_or_else(|| usage()),
+        None => camera::DEFAULT_BOX,
+    };
+
+    for path in &args.paths {
+        let log = read(path);
+        // The resolution the game rendered at if it was logged, since that's what a capture of it will be.
+        let resolution = resolution
+            .or_else(|| camera::logged_resolution(&log))
+            .unwrap_or(Resolution { width: 1920, height: 1080 });
+        let boxes = camera::bounding_boxes(&log, size, resolution, eye);
+        let out_path = output_path(path, args.value("--out-dir"), ".bboxes.csv");
+        let mut out = BufWriter::new(File::create(&out_path)?);
+        writeln!(out, "frame,fighter_id,x_min,y_min,x_max,y_max")?;
+        for bounds in &boxes {
+            writeln!(
+                out,
+                "{},{},{:.1},{:.1},{:.1},{:.1}",
+                bounds.frame, bounds.fighter_id, bounds.x_min, bounds.y_min, bounds.x_max, bounds.y_max
+            )?;
+        }
+        println!("{} -> {} ({} boxes)", path, out_path.display(), boxes.len());
+    }
+    Ok(())
+}
+
+// Writes a `.sync.csv` mapping each log frame to the video frame it was shown on.
+fn sync(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let (times_path, sync_at) = match (args.value("--video-times"), args.value("--sync-at")) {
+        (Some(times_path), Some(sync_at)) => (times_path, sync_at.parse::<f64>().unwrap_or_else(|_| usage())),
+        _ => usage(),
+    };
+    let video_times = video_sync::read_video_times(times_path)?;
+
+    for path in &args.paths {
+        let mappings = video_sync::map_frames(&read(path), &video_times, sync_at)?;
+        let out_path = output_path(path, args.value("--out-dir"), ".sync.csv");
+        let mut out = BufWriter::new(File::create(&out_path)?);
+        writeln!(out, "log_frame,video_frame,video_time,error_ms")?;
+        for mapping in &mappings {
+            writeln!(
+                out,
+                "{},{},{:.6},{:.2}",
+                mapping.log_frame, mapping.video_frame, mapping.video_time, mapping.error_ms
+            )?;
+        }
+        println!("{} -> {} ({} frames mapped)", path, out_path.display(), mappings.len());
+    }
+    Ok(())
+}
+
+// Prints a labels file line for each name or hash, so new names can be appended to a labels file.
+fn hash(args: &Args) {
+    let labels = labels(args);
+    let mut unknown = false;
+    for arg in &args.paths {
+        match hash40::parse_hash(arg) {
+            Some(hash) => match labels.get(hash) {
+                Some(label) => println!("{:#012x},{}", hash, label),
+                None => {
+                    eprintln!("{:#012x}: no label", hash);
+                    unknown = true;
+                }
+            },
+            None => println!("{:#012x},{}", hash40::hash40(arg), arg),
+        }
+    }
+    if unknown {
+        process::exit(1);
+    }
+}
+
+fn read(path: &str) -> Match {
+    match Match::read(path) {
+        Ok(log) => log,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            process::exit(2);
+        }
+    }
+}
+
+// Where to write a converted file: next to the log, or in `--out-dir`, with the extension swapped.
+fn output_path(log_path: &str, out_dir: Option<&str>, suffix: &str) -> PathBuf {
+    let log_path = Path::new(log_path);
+    let stem = log_path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("match");
+    let dir = match out_dir {
+        Some(out_dir) => PathBuf::from(out_dir),
+        None => log_path.parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+    dir.join(format!("{}{}", stem, suffix))
+}
+
+fn convert(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let format = args.value("--format").unwrap_or("parquet");
+    if format != "parquet" && format != "arrow" {
+        usage();
+    }
+
+    for path in &args.paths {
+        let log = read(path);
+        let mut batches = vec![(columnar::fighter_batch(&log)?, format!(".{}", format))];
+        if args.has("--events") {
+            batches.push((columnar::event_batch(&log)?, format!(".events.{}", format)));
+        }
+        for (batch, suffix) in batches {
+            let out_path = output_path(path, args.value("--out-dir"), &suffix);
+            if format == "parquet" {
+                columnar::write_parquet(&batch, &out_path)?;
+            } else {
+                columnar::write_arrow(&batch, &out_path)?;
+            }
+            println!("{} -> {} ({} rows)", path, out_path.display(), batch.num_rows());
+        }
+    }
+    Ok(())
+}
+
+fn export_csv(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let options = CsvOptions {
+        layout: if args.has("--wide") { Layout::PerFrame } else { Layout::PerFighter },
+        fields: args
+            .value("--fields")
+            .map(|fields| fields.split(',').map(|field| field.trim().to_string()).collect()),
+    };
+
+    for path in &args.paths {
+        let log = read(path);
+        let out_path = output_path(path, args.value("--out-dir"), ".csv");
+        let rows = csv_export::write_csv(&log, &options, BufWriter::new(File::create(&out_path)?))?;
+        println!("{} -> {} ({} rows)", path, out_path.display(), rows);
+    }
+    Ok(())
+}
+
+fn import(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = match args.value("--db") {
+        Some(db_path) => db_path,
+        None => usage(),
+    };
+    let mut connection = sqlite::open(db_path)?;
+
+    for path in &args.paths {
+        let log = read(path);
+        // Anything that isn't a match log, like a stray text file in the folder, reads as no frames.
+        if log.frames.is_empty() {
+            eprintln!("{}: no frames, not importing", path);
+            continue;
+        }
+        let replay_id = sqlite::match_key(&log, path);
+        let result = sqlite::import_match(&mut connection, &log, &replay_id, Some(path), args.has("--force"))?;
+        println!("{}: {:?} as {}", path, result, replay_id);
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => usage(),
+    };
+    let args = Args::parse(rest);
+
+    match command {
+        "summary" => {
+            let names = names(&args);
+            for path in &args.paths {
+                let summary = MatchSummary::from_match(&read(path), &names);
+                if args.has("--json") {
+                    println!("{}", serde_json::to_string(&summary).unwrap());
+                } else {
+                    print_summary(path, &summary);
+                }
+            }
+        }
+        "validate" => {
+            let mut failed = false;
+            for path in &args.paths {
+                let issues = validate(&read(path));
+                if issues.is_empty() {
+                    println!("{}: ok", path);
+                    continue;
+                }
+                failed = true;
+                println!("{}: {} issues", path, issues.len());
+                for issue in issues {
+                    println!("  {}", issue);
+                }
+            }
+            if failed {
+                process::exit(1);
+            }
+        }
+        "convert" => {
+            if let Err(e) = convert(&args) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        "csv" => {
+            if let Err(e) = export_csv(&args) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        "import" => {
+            if let Err(e) = import(&args) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        "stats" => {
+            if let Err(e) = stats(&args) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        "conversions" => print_conversions(&args),
+        "framedata" => print_framedata(&args),
+        "techniques" => print_techniques(&args),
+        "positions" => print_positions(&args),
+        "bboxes" => {
+            if let Err(e) = bboxes(&args) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        "sync" => {
+            if let Err(e) = sync(&args) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        "hash" => hash(&args),
+        _ => usage(),
+    }
+}
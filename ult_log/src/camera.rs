@@ -0,0 +1,193 @@
+// Projects world positions onto the screen using the camera logged each frame, so fighters can be located in
+// recorded video.
+//
+// The camera is a pinhole looking from its position at its target with world up as up, and the fov is the
+// vertical field of view in degrees. The aspect ratio comes from the output resolution. The frame's "camera"
+// event is used when there is one, otherwise the camera fields on the fighter record.
+
+use serde::{ Deserialize, Serialize };
+
+use crate::reader::Match;
+use crate::record::{ CameraBlock, FighterFrame, Frame, Vec3 };
+
+// Anything closer to the camera than this can't be projected.
+const NEAR: f32 = 0.1;
+
+// A fighter's size in world units, used for their bounding box when nothing better is given. The box sits on
+// their position, which is at their feet.
+pub const DEFAULT_BOX: BoxSize = BoxSize { width: 12.0, height: 16.0 };
+
+// Which camera position to project from. The internal position is only in logs with a camera block.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Eye {
+    #[default]
+    Position,
+    InternalPosition,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoxSize {
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Pixel {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub position: Vec3,
+    pub target: Vec3,
+    pub fov: f32,
+    pub up: Vec3,
+}
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    Vec3 { x: a.x - b.x, y: a.y - b.y, z: a.z - b.z }
+}
+
+fn dot(a: Vec3, b: Vec3) -> f32 {
+    a.x * b.x + a.y * b.y + a.z * b.z
+}
+
+fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    Vec3 { x: a.y * b.z - a.z * b.y, y: a.z * b.x - a.x * b.z, z: a.x * b.y - a.y * b.x }
+}
+
+fn normalize(a: Vec3) -> Option<Vec3> {
+    let length = dot(a, a).sqrt();
+    if length == 0.0 {
+        return None;
+    }
+    Some(Vec3 { x: a.x / length, y: a.y / length, z: a.z / length })
+}
+
+impl Camera {
+    pub fn from_fighter_frame(fighter: &FighterFrame) -> Camera {
+        Camera {
+            position: fighter.camera_position,
+            target: fighter.camera_target_position,
+            fov: fighter.camera_fov,
+            up: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+        }
+    }
+
+    pub fn from_block(block: &CameraBlock, eye: Eye) -> Camera {
+        Camera {
+            position: match eye {
+                Eye::Position => block.position,
+                Eye::InternalPosition => block.internal_position,
+            },
+            target: block.target_position,
+            fov: block.fov,
+            up: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+        }
+    }
+
+    // The camera for a frame, from its camera block if it has one.
+    pub fn for_frame(frame: &Frame, fighter: &FighterFrame, eye: Eye) -> Camera {
+        match frame.camera() {
+            Some(block) => Camera::from_block(&block, eye),
+            None => Camera::from_fighter_frame(fighter),
+        }
+    }
+
+    // Where `point` lands on a screen of `resolution`, with (0, 0) at the top left. None if it's behind the
+    // camera, or the camera block is empty (logs from before it was recorded). Points off screen are still
+    // returned, with coordinates outside the resolution.
+    pub fn project(&self, point: Vec3, resolution: Resolution) -> Option<Pixel> {
+        let forward = normalize(sub(self.target, self.position))?;
+        let right = normalize(cross(forward, self.up))?;
+        let up = cross(right, forward);
+
+        let relative = sub(point, self.position);
+        let depth = dot(relative, forward);
+        if depth < NEAR || self.fov <= 0.0 {
+            return None;
+        }
+
+        let half_height = (self.fov.to_radians() / 2.0).tan() * depth;
+        let half_width = half_height * resolution.width as f32 / resolution.height as f32;
+        let ndc_x = dot(relative, right) / half_width;
+        let ndc_y = dot(relative, up) / half_height;
+        Some(Pixel {
+            x: (ndc_x + 1.0) / 2.0 * resolution.width as f32,
+            y: (1.0 - ndc_y) / 2.0 * resolution.height as f32,
+        })
+    }
+}
+
+// A fighter's on-screen box, clipped to the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub frame: usize,
+    pub fighter_id: i32,
+    pub x_min: f32,
+    pub y_min: f32,
+    pub x_max: f32,
+    pub y_max: f32,
+}
+
+pub fn bounding_box(
+    camera: &Camera,
+    fighter: &FighterFrame,
+    frame: usize,
+    size: BoxSize,
+    resolution: Resolution,
+) -> Option<BoundingBox> {
+    let half_width = size.width / 2.0;
+    let corners = [
+        (fighter.pos_x - half_width, fighter.pos_y),
+        (fighter.pos_x + half_width, fighter.pos_y),
+        (fighter.pos_x - half_width, fighter.pos_y + size.height),
+        (fighter.pos_x + half_width, fighter.pos_y + size.height),
+    ];
+    let pixels = corners
+        .iter()
+        .map(|(x, y)| camera.project(Vec3 { x: *x, y: *y, z: 0.0 }, resolution))
+        .collect::<Option<Vec<Pixel>>>()?;
+
+    let clip_x = |x: f32| x.clamp(0.0, resolution.width as f32);
+    let clip_y = |y: f32| y.clamp(0.0, resolution.height as f32);
+    let bounds = BoundingBox {
+        frame,
+        fighter_id: fighter.fighter_id,
+        x_min: clip_x(pixels.iter().map(|pixel| pixel.x).fold(f32::INFINITY, f32::min)),
+        y_min: clip_y(pixels.iter().map(|pixel| pixel.y).fold(f32::INFINITY, f32::min)),
+        x_max: clip_x(pixels.iter().map(|pixel| pixel.x).fold(f32::NEG_INFINITY, f32::max)),
+        y_max: clip_y(pixels.iter().map(|pixel| pixel.y).fold(f32::NEG_INFINITY, f32::max)),
+    };
+    // Entirely off screen.
+    if bounds.x_min >= bounds.x_max || bounds.y_min >= bounds.y_max {
+        return None;
+    }
+    Some(bounds)
+}
+
+// Every visible fighter's box on every frame.
+pub fn bounding_boxes(log: &Match, size: BoxSize, resolution: Resolution, eye: Eye) -> Vec<BoundingBox> {
+    log.frames
+        .iter()
+        .flat_map(|frame| {
+            frame.fighters.iter().filter_map(move |fighter| {
+                let camera = Camera::for_frame(frame, fighter, eye);
+                bounding_box(&camera, fighter, frame.index, size, resolution)
+            })
+        })
+        .collect()
+}
+
+// The resolution the game was rendering at, from the first camera block in the log.
+pub fn logged_resolution(log: &Match) -> Option<Resolution> {
+    let block = log.frames.iter().find_map(|frame| frame.camera())?;
+    Some(Resolution { width: block.screen_width, height: block.screen_height })
+}
@@ -0,0 +1,215 @@
+// Flatten match logs into Arrow record batches, and write them out as Parquet or Arrow IPC files.
+//
+// Fighter frames become one row per fighter per frame, with nested camera blocks split into `_x`/`_y`/`_z`
+// columns. The column order and types are stable so downstream dataframes can rely on them.
+
+use arrow_array::builder::{ BooleanBuilder, Float32Builder, Int32Builder, StringBuilder, UInt32Builder, UInt64Builder, UInt8Builder };
+use arrow_array::{ ArrayRef, RecordBatch };
+use arrow_schema::{ ArrowError, DataType, Field, Schema, SchemaRef };
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::reader::Match;
+
+#[derive(Debug)]
+pub enum ConvertError {
+    Io(io::Error),
+    Arrow(ArrowError),
+    Parquet(ParquetError),
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::Io(e) => write!(f, "couldn't write file: {}", e),
+            ConvertError::Arrow(e) => write!(f, "couldn't build arrow data: {}", e),
+            ConvertError::Parquet(e) => write!(f, "couldn't write parquet: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+impl From<io::Error> for ConvertError {
+    fn from(e: io::Error) -> Self {
+        ConvertError::Io(e)
+    }
+}
+
+impl From<ArrowError> for ConvertError {
+    fn from(e: ArrowError) -> Self {
+        ConvertError::Arrow(e)
+    }
+}
+
+impl From<ParquetError> for ConvertError {
+    fn from(e: ParquetError) -> Self {
+        ConvertError::Parquet(e)
+    }
+}
+
+pub fn fighter_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("replay_id", DataType::Utf8, true),
+        Field::new("frame", DataType::UInt32, false),
+        Field::new("num_frames_left", DataType::UInt32, false),
+        Field::new("fighter_id", DataType::Int32, false),
+        Field::new("fighter_name", DataType::Int32, false),
+        Field::new("stock_count", DataType::UInt8, false),
+        Field::new("status_kind", DataType::Int32, false),
+        Field::new("motion_kind", DataType::UInt64, false),
+        Field::new("damage", DataType::Float32, false),
+        Field::new("shield_size", DataType::Float32, false),
+        Field::new("facing", DataType::Float32, false),
+        Field::new("pos_x", DataType::Float32, false),
+        Field::new("pos_y", DataType::Float32, false),
+        Field::new("hitstun_left", DataType::Float32, false),
+        Field::new("attack_connected", DataType::Boolean, false),
+        Field::new("animation_frame_num", DataType::Float32, false),
+        Field::new("can_act", DataType::Boolean, false),
+        Field::new("camera_position_x", DataType::Float32, false),
+        Field::new("camera_position_y", DataType::Float32, false),
+        Field::new("camera_position_z", DataType::Float32, false),
+        Field::new("camera_target_position_x", DataType::Float32, false),
+        Field::new("camera_target_position_y", DataType::Float32, false),
+        Field::new("camera_target_position_z", DataType::Float32, false),
+        Field::new("camera_fov", DataType::Float32, false),
+        Field::new("stage_id", DataType::Int32, false),
+    ]))
+}
+
+pub fn event_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("replay_id", DataType::Utf8, true),
+        Field::new("frame", DataType::UInt32, false),
+        Field::new("event", DataType::Utf8, false),
+        // The rest of the event's fields, as a JSON object.
+        Field::new("fields", DataType::Utf8, false),
+    ]))
+}
+
+fn f32_column<'a>(values: impl Iterator<Item = &'a f32>) -> ArrayRef {
+    let mut builder = Float32Builder::new();
+    for value in values {
+        builder.append_value(*value);
+    }
+    Arc::new(builder.finish())
+}
+
+fn i32_column<'a>(values: impl Iterator<Item = &'a i32>) -> ArrayRef {
+    let mut builder = Int32Builder::new();
+    for value in values {
+        builder.append_value(*value);
+    }
+    Arc::new(builder.finish())
+}
+
+fn bool_column<'a>(values: impl Iterator<Item = &'a bool>) -> ArrayRef {
+    let mut builder = BooleanBuilder::new();
+    for value in values {
+        builder.append_value(*value);
+    }
+    Arc::new(builder.finish())
+}
+
+fn replay_id_column(replay_id: Option<&str>, rows: usize) -> ArrayRef {
+    let mut builder = StringBuilder::new();
+    for _ in 0..rows {
+        builder.append_option(replay_id);
+    }
+    Arc::new(builder.finish())
+}
+
+pub fn fighter_batch(log: &Match) -> Result<RecordBatch, ConvertError> {
+    let rows: Vec<(u32, &crate::record::FighterFrame)> = log
+        .frames
+        .iter()
+        .flat_map(|frame| frame.fighters.iter().map(move |fighter| (frame.index as u32, fighter)))
+        .collect();
+    let fighters = || rows.iter().map(|(_, fighter)| *fighter);
+
+    let mut frame = UInt32Builder::new();
+    let mut num_frames_left = UInt32Builder::new();
+    let mut stock_count = UInt8Builder::new();
+    let mut motion_kind = UInt64Builder::new();
+    for (index, fighter) in &rows {
+        frame.append_value(*index);
+        num_frames_left.append_value(fighter.num_frames_left);
+        stock_count.append_value(fighter.stock_count);
+        motion_kind.append_value(fighter.motion_kind);
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        replay_id_column(log.header.replay_id.as_deref(), rows.len()),
+        Arc::new(frame.finish()),
+        Arc::new(num_frames_left.finish()),
+        i32_column(fighters().map(|fighter| &fighter.fighter_id)),
+        i32_column(fighters().map(|fighter| &fighter.fighter_name)),
+        Arc::new(stock_count.finish()),
+        i32_column(fighters().map(|fighter| &fighter.status_kind)),
+        Arc::new(motion_kind.finish()),
+        f32_column(fighters().map(|fighter| &fighter.damage)),
+        f32_column(fighters().map(|fighter| &fighter.shield_size)),
+        f32_column(fighters().map(|fighter| &fighter.facing)),
+        f32_column(fighters().map(|fighter| &fighter.pos_x)),
+        f32_column(fighters().map(|fighter| &fighter.pos_y)),
+        f32_column(fighters().map(|fighter| &fighter.hitstun_left)),
+        bool_column(fighters().map(|fighter| &fighter.attack_connected)),
+        f32_column(fighters().map(|fighter| &fighter.animation_frame_num)),
+        bool_column(fighters().map(|fighter| &fighter.can_act)),
+        f32_column(fighters().map(|fighter| &fighter.camera_position.x)),
+        f32_column(fighters().map(|fighter| &fighter.camera_position.y)),
+        f32_column(fighters().map(|fighter| &fighter.camera_position.z)),
+        f32_column(fighters().map(|fighter| &fighter.camera_target_position.x)),
+        f32_column(fighters().map(|fighter| &fighter.camera_target_position.y)),
+        f32_column(fighters().map(|fighter| &fighter.camera_target_position.z)),
+        f32_column(fighters().map(|fighter| &fighter.camera_fov)),
+        i32_column(fighters().map(|fighter| &fighter.stage_id)),
+    ];
+    Ok(RecordBatch::try_new(fighter_schema(), columns)?)
+}
+
+pub fn event_batch(log: &Match) -> Result<RecordBatch, ConvertError> {
+    let mut rows = 0;
+    let mut frame = UInt32Builder::new();
+    let mut event = StringBuilder::new();
+    let mut fields = StringBuilder::new();
+    for log_frame in &log.frames {
+        for log_event in &log_frame.events {
+            rows += 1;
+            frame.append_value(log_frame.index as u32);
+            event.append_value(&log_event.event);
+            fields.append_value(serde_json::Value::Object(log_event.fields.clone()).to_string());
+        }
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        replay_id_column(log.header.replay_id.as_deref(), rows),
+        Arc::new(frame.finish()),
+        Arc::new(event.finish()),
+        Arc::new(fields.finish()),
+    ];
+    Ok(RecordBatch::try_new(event_schema(), columns)?)
+}
+
+pub fn write_parquet<P: AsRef<Path>>(batch: &RecordBatch, path: P) -> Result<(), ConvertError> {
+    let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut writer = ArrowWriter::try_new(File::create(path)?, batch.schema(), Some(properties))?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+pub fn write_arrow<P: AsRef<Path>>(batch: &RecordBatch, path: P) -> Result<(), ConvertError> {
+    let mut writer = arrow_ipc::writer::FileWriter::try_new(File::create(path)?, &batch.schema())?;
+    writer.write(batch)?;
+    writer.finish()?;
+    Ok(())
+}
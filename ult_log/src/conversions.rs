@@ -0,0 +1,160 @@
+// Splits a match into conversions: runs of hits by one player on another, from the opening hit until the
+// defender has been out of hitstun for long enough to reset to neutral, or loses a stock.
+
+use serde::{ Deserialize, Serialize };
+
+use crate::reader::Match;
+use crate::record::{ FighterFrame, Frame };
+
+// How long the defender can be out of hitstun before the next hit counts as a new opening.
+pub const RESET_FRAMES: usize = 45;
+
+// Damage changes smaller than this are float noise rather than a hit.
+const DAMAGE_EPSILON: f32 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConversionOptions {
+    pub reset_frames: usize,
+}
+
+impl Default for ConversionOptions {
+    fn default() -> Self {
+        ConversionOptions { reset_frames: RESET_FRAMES }
+    }
+}
+
+// One hit in a conversion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hit {
+    pub frame: usize,
+    // The attacker's motion when the hit landed.
+    pub motion_kind: u64,
+    pub damage: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conversion {
+    pub attacker_id: i32,
+    pub defender_id: i32,
+    // Frame indexes of the opening hit and the last frame the defender was in hitstun or hit.
+    pub start_frame: usize,
+    pub end_frame: usize,
+    pub start_percent: f32,
+    pub end_percent: f32,
+    // Every hit in order, starting with the opening.
+    pub hits: Vec<Hit>,
+    // Whether the attacker opened this up from neutral, rather than while being punished themselves.
+    pub neutral_win: bool,
+    pub killed: bool,
+}
+
+impl Conversion {
+    pub fn damage(&self) -> f32 {
+        self.end_percent - self.start_percent
+    }
+
+    pub fn opening_motion_kind(&self) -> u64 {
+        self.hits.first().map(|hit| hit.motion_kind).unwrap_or(0)
+    }
+
+    // The moves used, with repeated hits from the same move (multi-hits) counted once.
+    pub fn moves(&self) -> Vec<u64> {
+        let mut moves: Vec<u64> = self.hits.iter().map(|hit| hit.motion_kind).collect();
+        moves.dedup();
+        moves
+    }
+}
+
+// A conversion that hasn't ended yet, and the last frame that kept it going.
+struct Open {
+    conversion: Conversion,
+    last_active_frame: usize,
+}
+
+// Whoever landed the hit on `defender`. In anything but a 1v1 we go with whoever had a hit connect this frame.
+fn attacker<'a>(frame: &'a Frame, defender: &FighterFrame) -> Option<&'a FighterFrame> {
+    let mut others = frame.fighters.iter().filter(|fighter| fighter.fighter_id != defender.fighter_id);
+    let first = others.clone().next();
+    others.find(|fighter| fighter.attack_connected).or(first)
+}
+
+pub fn find_conversions(log: &Match, options: &ConversionOptions) -> Vec<Conversion> {
+    let mut conversions = Vec::new();
+    // Indexed by position in the header's fighter list.
+    let fighter_ids: Vec<i32> = log.header.fighters.iter().map(|info| info.fighter_id).collect();
+    let mut open: Vec<Option<Open>> = fighter_ids.iter().map(|_| None).collect();
+    let mut previous: Vec<Option<FighterFrame>> = fighter_ids.iter().map(|_| None).collect();
+
+    for frame in &log.frames {
+        for (slot, fighter_id) in fighter_ids.iter().enumerate() {
+            let defender = match frame.fighter(*fighter_id) {
+                Some(defender) => defender,
+                None => continue,
+            };
+            let before = match previous[slot].replace(defender.clone()) {
+                Some(before) => before,
+                None => continue,
+            };
+            let lost_stock = defender.stock_count < before.stock_count;
+            let hit = !lost_stock && defender.damage > before.damage + DAMAGE_EPSILON;
+            let attacker = if hit { attacker(frame, defender) } else { None };
+
+            if let Some(current) = &mut open[slot] {
+                if lost_stock {
+                    current.conversion.killed = true;
+                    current.conversion.end_frame = frame.index;
+                    conversions.push(open[slot].take().unwrap().conversion);
+                    continue;
+                }
+                // Someone else getting a hit in ends this conversion and starts theirs.
+                let same_attacker = attacker.map(|attacker| attacker.fighter_id == current.conversion.attacker_id);
+                if same_attacker != Some(false) && (hit || defender.hitstun_left > 0.0) {
+                    if let Some(attacker) = attacker {
+                        current.conversion.hits.push(Hit {
+                            frame: frame.index,
+                            motion_kind: attacker.motion_kind,
+                            damage: defender.damage - before.damage,
+                        });
+                    }
+                    current.last_active_frame = frame.index;
+                    current.conversion.end_frame = frame.index;
+                    current.conversion.end_percent = defender.damage;
+                    continue;
+                }
+                if same_attacker.is_none() && frame.index - current.last_active_frame <= options.reset_frames {
+                    continue;
+                }
+                conversions.push(open[slot].take().unwrap().conversion);
+            }
+
+            let attacker = match attacker {
+                Some(attacker) => attacker,
+                None => continue,
+            };
+            let attacker_slot = fighter_ids.iter().position(|id| *id == attacker.fighter_id);
+            let attacker_punished = attacker_slot.map(|slot| open[slot].is_some()).unwrap_or(false);
+            open[slot] = Some(Open {
+                conversion: Conversion {
+                    attacker_id: attacker.fighter_id,
+                    defender_id: defender.fighter_id,
+                    start_frame: frame.index,
+                    end_frame: frame.index,
+                    start_percent: before.damage,
+                    end_percent: defender.damage,
+                    hits: vec![Hit {
+                        frame: frame.index,
+                        motion_kind: attacker.motion_kind,
+                        damage: defender.damage - before.damage,
+                    }],
+                    neutral_win: !attacker_punished,
+                    killed: false,
+                },
+                last_active_frame: frame.index,
+            });
+        }
+    }
+
+    conversions.extend(open.into_iter().flatten().map(|current| current.conversion));
+    conversions.sort_by_key(|conversion| conversion.start_frame);
+    conversions
+}
@@ -0,0 +1,202 @@
+// Export match logs as CSV, for anyone without Parquet tooling.
+//
+// Nested fields are flattened with dots (`camera_position.x`). Rows are either one per fighter per frame, or one
+// wide row per frame with each fighter's fields prefixed by their player number (`p1.damage`).
+
+use serde_json::Value;
+use std::fmt;
+use std::io::{ self, Write };
+
+use crate::reader::Match;
+use crate::record::{ FighterFrame, Frame };
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    PerFighter,
+    PerFrame,
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub layout: Layout,
+    // Which fields to write, or None for all of them. A group name like `camera_position` selects all of its
+    // subfields.
+    pub fields: Option<Vec<String>>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions { layout: Layout::PerFighter, fields: None }
+    }
+}
+
+#[derive(Debug)]
+pub enum CsvError {
+    Io(io::Error),
+    UnknownField(String),
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Io(e) => write!(f, "couldn't write csv: {}", e),
+            CsvError::UnknownField(field) => write!(f, "unknown field {:?}", field),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+impl From<io::Error> for CsvError {
+    fn from(e: io::Error) -> Self {
+        CsvError::Io(e)
+    }
+}
+
+// Fields that are the same for every fighter on a frame. In the wide layout these are only written once.
+const FRAME_FIELDS: [&str; 5] = ["num_frames_left", "stage_id", "camera_position", "camera_target_position", "camera_fov"];
+
+fn is_frame_field(field: &str) -> bool {
+    FRAME_FIELDS.iter().any(|group| field == *group || field.starts_with(&format!("{}.", group)))
+}
+
+fn flatten_into(prefix: &str, value: &Value, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let name = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten_into(&name, value, out);
+            }
+        }
+        value => out.push((prefix.to_string(), value.clone())),
+    }
+}
+
+// Every field of a fighter frame as (column name, value), in declaration order. Optional groups are always
+// present since missing ones are filled with defaults when the log is read.
+pub fn flatten(fighter: &FighterFrame) -> Vec<(String, Value)> {
+    let mut out = Vec::new();
+    flatten_into("", &serde_json::to_value(fighter).unwrap_or(Value::Null), &mut out);
+    out
+}
+
+fn all_fields() -> Vec<String> {
+    let empty: FighterFrame = serde_json::from_value(serde_json::json!({
+        "num_frames_left": 0, "fighter_id": 0, "fighter_name": 0, "stock_count": 0, "status_kind": 0,
+        "motion_kind": 0, "damage": 0.0, "shield_size": 0.0, "facing": 0.0, "pos_x": 0.0, "pos_y": 0.0,
+        "hitstun_left": 0.0, "attack_connected": false, "animation_frame_num": 0.0, "can_act": false,
+        "stage_id": 0,
+    }))
+    .expect("every required field is listed");
+    flatten(&empty).into_iter().map(|(name, _)| name).collect()
+}
+
+fn selected_fields(options: &CsvOptions) -> Result<Vec<String>, CsvError> {
+    let all = all_fields();
+    let requested = match &options.fields {
+        None => return Ok(all),
+        Some(requested) => requested,
+    };
+
+    let mut selected = Vec::new();
+    for field in requested {
+        let group_prefix = format!("{}.", field);
+        let matches: Vec<&String> = all.iter().filter(|name| *name == field || name.starts_with(&group_prefix)).collect();
+        if matches.is_empty() {
+            return Err(CsvError::UnknownField(field.clone()));
+        }
+        for name in matches {
+            if !selected.contains(name) {
+                selected.push(name.clone());
+            }
+        }
+    }
+    Ok(selected)
+}
+
+fn escape(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => escape(s),
+        Some(value) => value.to_string(),
+    }
+}
+
+fn lookup<'a>(flat: &'a [(String, Value)], field: &str) -> Option<&'a Value> {
+    flat.iter().find(|(name, _)| name == field).map(|(_, value)| value)
+}
+
+fn write_row<W: Write>(out: &mut W, cells: &[String]) -> io::Result<()> {
+    writeln!(out, "{}", cells.join(","))
+}
+
+// Write the log as CSV, returning how many rows were written (not counting the header).
+pub fn write_csv<W: Write>(log: &Match, options: &CsvOptions, mut out: W) -> Result<usize, CsvError> {
+    let fields = selected_fields(options)?;
+    let replay_id = escape(log.header.replay_id.as_deref().unwrap_or(""));
+    let mut rows = 0;
+
+    match options.layout {
+        Layout::PerFighter => {
+            let mut header = vec!["replay_id".to_string(), "frame".to_string()];
+            header.extend(fields.iter().cloned());
+            write_row(&mut out, &header)?;
+
+            for frame in &log.frames {
+                for fighter in &frame.fighters {
+                    let flat = flatten(fighter);
+                    let mut cells = vec![replay_id.clone(), frame.index.to_string()];
+                    cells.extend(fields.iter().map(|field| cell(lookup(&flat, field))));
+                    write_row(&mut out, &cells)?;
+                    rows += 1;
+                }
+            }
+        }
+        Layout::PerFrame => {
+            let frame_fields: Vec<&String> = fields.iter().filter(|field| is_frame_field(field)).collect();
+            let fighter_fields: Vec<&String> = fields.iter().filter(|field| !is_frame_field(field)).collect();
+            let fighter_ids: Vec<i32> = log.header.fighters.iter().map(|info| info.fighter_id).collect();
+
+            let mut header = vec!["replay_id".to_string(), "frame".to_string()];
+            header.extend(frame_fields.iter().map(|field| field.to_string()));
+            for fighter_id in &fighter_ids {
+                header.extend(fighter_fields.iter().map(|field| format!("p{}.{}", fighter_id + 1, field)));
+            }
+            write_row(&mut out, &header)?;
+
+            for frame in &log.frames {
+                write_row(&mut out, &wide_row(frame, &replay_id, &frame_fields, &fighter_fields, &fighter_ids))?;
+                rows += 1;
+            }
+        }
+    }
+    Ok(rows)
+}
+
+fn wide_row(
+    frame: &Frame,
+    replay_id: &str,
+    frame_fields: &[&String],
+    fighter_fields: &[&String],
+    fighter_ids: &[i32]
+) -> Vec<String> {
+    let mut cells = vec![replay_id.to_string(), frame.index.to_string()];
+
+    let first = frame.fighters.first().map(flatten).unwrap_or_default();
+    cells.extend(frame_fields.iter().map(|field| cell(lookup(&first, field))));
+
+    for fighter_id in fighter_ids {
+        // A fighter missing from this frame leaves its columns empty.
+        let flat = frame.fighter(*fighter_id).map(flatten).unwrap_or_default();
+        cells.extend(fighter_fields.iter().map(|field| cell(lookup(&flat, field))));
+    }
+    cells
+}
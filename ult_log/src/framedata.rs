@@ -0,0 +1,296 @@
+// Frame data measured from recordings: how long each move takes to hit, how long until the fighter can act
+// again, and how safe it is on shield.
+//
+// A move is a run of frames with the same `motion_kind` that starts with the fighter unable to act. It ends when
+// the motion changes or `animation_frame_num` goes backwards (the same move used again). Startup is the first frame
+// the move hits (`attack_connected`) or hits a shield, which `attack_connected` doesn't cover and is spotted from
+// the defender's shield instead. Any one recording only gives a few samples per move, so these are meant to be
+// aggregated across many.
+
+use serde::{ Deserialize, Serialize };
+use std::collections::BTreeMap;
+
+use crate::name_tables::NameTables;
+use crate::reader::Match;
+use crate::record::FighterFrame;
+
+// Give up looking for the fighter to become actionable after this long, as they've probably been hit out of it.
+const MAX_MOVE_FRAMES: usize = 300;
+
+// A held shield wears down a little every frame on its own. A hit takes off more than this.
+const SHIELD_HIT_MIN_DROP: f32 = 0.5;
+
+// FIGHTER_STATUS_KIND_GUARD_DAMAGE, the shield stun a shielded hit puts the defender in.
+const STATUS_GUARD_DAMAGE: i32 = 0x1e;
+
+// What we measured for one use of a move.
+#[derive(Debug, Clone, PartialEq)]
+struct Sample {
+    // Frames counted from 1 on the first frame of the move.
+    startup: Option<usize>,
+    total_frames: Option<usize>,
+    // Whether the first contact was with a shield rather than a hit.
+    shielded: bool,
+    on_shield: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MoveFrameData {
+    pub fighter_name: i32,
+    pub fighter: Option<String>,
+    pub motion_kind: u64,
+    pub motion: Option<String>,
+    // How many times we saw the move, and how many of those hit or were shielded.
+    pub samples: usize,
+    pub hit_samples: usize,
+    pub shield_samples: usize,
+    // The medians over every sample we could measure.
+    pub startup: Option<usize>,
+    pub total_frames: Option<usize>,
+    pub on_shield: Option<i64>,
+}
+
+// Samples for every move, keyed by fighter kind and motion.
+#[derive(Debug, Clone, Default)]
+pub struct FrameDataTable {
+    moves: BTreeMap<(i32, u64), Vec<Sample>>,
+}
+
+fn median<T: Copy + Ord>(values: impl Iterator<Item = T>) -> Option<T> {
+    let mut values: Vec<T> = values.collect();
+    values.sort();
+    values.get(values.len() / 2).copied()
+}
+
+// Index of the first frame from `start` on which `fighter_id` can act.
+fn actionable_at(log: &Match, fighter_id: i32, start: usize) -> Option<usize> {
+    log.frames
+        .iter()
+        .skip(start)
+        .take(MAX_MOVE_FRAMES)
+        .find(|frame| frame.fighter(fighter_id).map(|fighter| fighter.can_act).unwrap_or(false))
+        .map(|frame| frame.index)
+}
+
+// Someone other than `attacker_id` whose shield took a hit on frame `index`: their shield shrank by more than it
+// wears down on its own, or they went into shield stun.
+fn shielding_defender(log: &Match, attacker_id: i32, index: usize) -> Option<i32> {
+    let before = log.frames.get(index.checked_sub(1)?)?;
+    log.frames[index]
+        .fighters
+        .iter()
+        .filter(|fighter| fighter.fighter_id != attacker_id)
+        .find(|fighter| match before.fighter(fighter.fighter_id) {
+            Some(previous) => {
+                fighter.shield_size < previous.shield_size - SHIELD_HIT_MIN_DROP
+                    || (fighter.status_kind == STATUS_GUARD_DAMAGE && previous.status_kind != STATUS_GUARD_DAMAGE)
+            }
+            None => false,
+        })
+        .map(|fighter| fighter.fighter_id)
+}
+
+fn measure(log: &Match, fighter_id: i32, start: usize, end: usize) -> Sample {
+    let mut sample = Sample { startup: None, total_frames: None, shielded: false, on_shield: None };
+    // The first frame the move hit someone, or hit someone's shield (with who).
+    let contact = (start..end).find_map(|index| {
+        if log.frames[index].fighter(fighter_id)?.attack_connected {
+            return Some((index, None));
+        }
+        shielding_defender(log, fighter_id, index).map(|defender_id| (index, Some(defender_id)))
+    });
+    let actionable = actionable_at(log, fighter_id, start);
+    sample.total_frames = actionable.map(|actionable| actionable - start);
+
+    if let Some((contact, defender)) = contact {
+        sample.startup = Some(contact - start + 1);
+        if let Some(defender_id) = defender {
+            sample.shielded = true;
+            sample.on_shield = actionable.and_then(|actionable| {
+                actionable_at(log, defender_id, contact + 1)
+                    .map(|defender_actionable| defender_actionable as i64 - actionable as i64)
+            });
+        }
+    }
+    sample
+}
+
+impl FrameDataTable {
+    pub fn new() -> FrameDataTable {
+        FrameDataTable::default()
+    }
+
+    // Adds a sample for every move used in the match.
+    pub fn add_match(&mut self, log: &Match) {
+        for info in &log.header.fighters {
+            // The start and first frame of the move we're in, and the animation frame of the frame before this one.
+            let mut current: Option<(usize, FighterFrame)> = None;
+            let mut last_animation_frame = 0.0;
+            for frame in &log.frames {
+                let fighter = match frame.fighter(info.fighter_id) {
+                    Some(fighter) => fighter,
+                    None => continue,
+                };
+                let continues = match &current {
+                    Some((_, first)) => {
+                        first.motion_kind == fighter.motion_kind && fighter.animation_frame_num >= last_animation_frame
+                    }
+                    None => false,
+                };
+                last_animation_frame = fighter.animation_frame_num;
+                if continues {
+                    continue;
+                }
+                if let Some((start, first)) = current.take() {
+                    self.add_sample(log, &first, start, frame.index);
+                }
+                current = Some((frame.index, fighter.clone()));
+            }
+            if let Some((start, first)) = current {
+                self.add_sample(log, &first, start, log.frames.len());
+            }
+        }
+    }
+
+    fn add_sample(&mut self, log: &Match, first: &FighterFrame, start: usize, end: usize) {
+        // Standing around, walking and the like aren't moves.
+        if first.can_act {
+            return;
+        }
+        let sample = measure(log, first.fighter_id, start, end);
+        self.moves.entry((first.fighter_name, first.motion_kind)).or_default().push(sample);
+    }
+
+    // One row per fighter and move that hit or was shielded at least once, sorted by fighter then startup.
+    pub fn rows(&self, names: &NameTables) -> Vec<MoveFrameData> {
+        let mut rows: Vec<MoveFrameData> = self
+            .moves
+            .iter()
+            .filter(|(_, samples)| samples.iter().any(|sample| sample.startup.is_some()))
+            .map(|((fighter_name, motion_kind), samples)| MoveFrameData {
+                fighter_name: *fighter_name,
+                fighter: names.fighter(*fighter_name).map(str::to_string),
+                motion_kind: *motion_kind,
+                motion: names.motion(*motion_kind).map(str::to_string),
+                samples: samples.len(),
+                hit_samples: samples.iter().filter(|sample| sample.startup.is_some() && !sample.shielded).count(),
+                shield_samples: samples.iter().filter(|sample| sample.shielded).count(),
+                startup: median(samples.iter().filter_map(|sample| sample.startup)),
+                total_frames: median(samples.iter().filter_map(|sample| sample.total_frames)),
+                on_shield: median(samples.iter().filter_map(|sample| sample.on_shield)),
+            })
+            .collect();
+        rows.sort_by_key(|row| (row.fighter_name, row.startup, row.motion_kind));
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::{ FighterInfo, Frame, MatchHeader, Vec3 };
+
+    const WAIT: u64 = 0x0553c117c3;
+    const ATTACK: u64 = 0x0c3495ada5;
+    const STATUS_GUARD: i32 = 0x1c;
+
+    fn fighter(fighter_id: i32, motion_kind: u64, animation_frame_num: f32, can_act: bool) -> FighterFrame {
+        FighterFrame {
+            num_frames_left: 0,
+            fighter_id,
+            fighter_name: fighter_id,
+            stock_count: 3,
+            status_kind: 0,
+            motion_kind,
+            damage: 0.0,
+            shield_size: 50.0,
+            facing: 1.0,
+            pos_x: 0.0,
+            pos_y: 0.0,
+            hitstun_left: 0.0,
+            attack_connected: false,
+            animation_frame_num,
+            can_act,
+            camera_position: Vec3::default(),
+            camera_target_position: Vec3::default(),
+            camera_fov: 0.0,
+            stage_id: 0,
+        }
+    }
+
+    fn log(frames: Vec<Vec<FighterFrame>>) -> Match {
+        Match {
+            header: MatchHeader {
+                fighters: (0..2).map(|fighter_id| FighterInfo { fighter_id, fighter_name: fighter_id }).collect(),
+                ..MatchHeader::default()
+            },
+            frames: frames
+                .into_iter()
+                .enumerate()
+                .map(|(index, fighters)| Frame { index, fighters, ..Frame::default() })
+                .collect(),
+            errors: vec![],
+        }
+    }
+
+    // Fighter 0 uses a move on frames 1-10 that hits fighter 1's shield on frame 5. Fighter 1's shield wears down
+    // a little every frame, and they're in shield stun on frames 5-7.
+    fn shielded_move() -> Match {
+        log((0..14)
+            .map(|index| {
+                let mut attacker = match index {
+                    1..=10 => fighter(0, ATTACK, index as f32, false),
+                    _ => fighter(0, WAIT, 0.0, true),
+                };
+                attacker.shield_size = 0.0;
+                let mut defender = fighter(1, WAIT, 0.0, true);
+                defender.status_kind = STATUS_GUARD;
+                defender.shield_size = 50.0 - index as f32 * 0.15;
+                if index >= 5 {
+                    defender.shield_size -= 8.0;
+                }
+                if (5..8).contains(&index) {
+                    defender.status_kind = STATUS_GUARD_DAMAGE;
+                    defender.can_act = false;
+                }
+                vec![attacker, defender]
+            })
+            .collect())
+    }
+
+    #[test]
+    fn measures_moves_that_only_hit_shield() {
+        let mut table = FrameDataTable::new();
+        table.add_match(&shielded_move());
+        let rows = table.rows(&NameTables::embedded());
+
+        assert_eq!(rows.len(), 1);
+        let row = &rows[0];
+        assert_eq!((row.fighter_name, row.motion_kind), (0, ATTACK));
+        assert_eq!((row.samples, row.hit_samples, row.shield_samples), (1, 0, 1));
+        assert_eq!(row.startup, Some(5));
+        assert_eq!(row.total_frames, Some(10));
+        // The defender can act on frame 8, three frames before the attacker on frame 11.
+        assert_eq!(row.on_shield, Some(-3));
+    }
+
+    #[test]
+    fn measures_startup_from_a_hit() {
+        let mut log = shielded_move();
+        for frame in &mut log.frames {
+            frame.fighters[1].shield_size = 50.0;
+            frame.fighters[1].status_kind = 0;
+            frame.fighters[1].can_act = true;
+        }
+        log.frames[3].fighters[0].attack_connected = true;
+
+        let mut table = FrameDataTable::new();
+        table.add_match(&log);
+        let rows = table.rows(&NameTables::embedded());
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!((rows[0].hit_samples, rows[0].shield_samples), (1, 0));
+        assert_eq!(rows[0].startup, Some(3));
+        assert_eq!(rows[0].on_shield, None);
+    }
+}
@@ -0,0 +1,159 @@
+// Hash40 hashing and reverse lookup, for turning `motion_kind` back into a motion name.
+//
+// A Hash40 is the CRC32 of a name in the low 32 bits with the name's length in the bits above it. Labels files
+// are the usual community format, one `0x0c3495ada5,attack_air_f` pair per line. A line with just a name is
+// hashed here, so a labels file can be extended by listing new names.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{ self, BufRead, BufReader };
+use std::path::Path;
+
+use crate::names;
+
+// Same as `smash::hash40`.
+pub fn hash40(name: &str) -> u64 {
+    let mut crc: u32 = 0xffff_ffff;
+    for byte in name.bytes() {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    (!crc as u64) | ((name.len() as u64) << 32)
+}
+
+pub fn parse_hash(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let hex = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X"))?;
+    u64::from_str_radix(hex, 16).ok()
+}
+
+#[derive(Debug)]
+pub enum LabelsError {
+    Io(io::Error),
+    Malformed { line: usize, message: String },
+}
+
+impl fmt::Display for LabelsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LabelsError::Io(e) => write!(f, "couldn't read labels: {}", e),
+            LabelsError::Malformed { line, message } => write!(f, "malformed labels line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for LabelsError {}
+
+impl From<io::Error> for LabelsError {
+    fn from(e: io::Error) -> Self {
+        LabelsError::Io(e)
+    }
+}
+
+// A label whose listed hash doesn't match the hash of its name. These aren't added to the dictionary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub line: usize,
+    pub listed: u64,
+    pub label: String,
+    pub actual: u64,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: {} is listed as {:#012x} but hashes to {:#012x}",
+            self.line, self.label, self.listed, self.actual
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Labels {
+    labels: HashMap<u64, String>,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl Labels {
+    // Just the motions from the embedded name tables.
+    pub fn embedded() -> Labels {
+        let mut labels = Labels::default();
+        for (hash, name) in names::MOTION_NAMES {
+            labels.labels.insert(*hash, name.to_string());
+        }
+        labels
+    }
+
+    pub fn parse<R: BufRead>(reader: R) -> Result<Labels, LabelsError> {
+        let mut labels = Labels::default();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_once(',') {
+                None => {
+                    labels.add(line);
+                }
+                Some((hash, label)) => {
+                    let listed = parse_hash(hash).ok_or_else(|| LabelsError::Malformed {
+                        line: index + 1,
+                        message: format!("{:?} isn't a hash", hash),
+                    })?;
+                    let label = label.trim();
+                    let actual = hash40(label);
+                    if actual == listed {
+                        labels.labels.insert(listed, label.to_string());
+                    } else {
+                        labels.mismatches.push(Mismatch { line: index + 1, listed, label: label.to_string(), actual });
+                    }
+                }
+            }
+        }
+        Ok(labels)
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Labels, LabelsError> {
+        Labels::parse(BufReader::new(File::open(path)?))
+    }
+
+    // Adds a name, returning its hash.
+    pub fn add(&mut self, name: &str) -> u64 {
+        let hash = hash40(name);
+        self.labels.insert(hash, name.to_string());
+        hash
+    }
+
+    // Adds everything from `other` that we don't already have a label for.
+    pub fn extend(&mut self, other: Labels) {
+        for (hash, label) in other.labels {
+            self.labels.entry(hash).or_insert(label);
+        }
+        self.mismatches.extend(other.mismatches);
+    }
+
+    pub fn get(&self, hash: u64) -> Option<&str> {
+        self.labels.get(&hash).map(String::as_str)
+    }
+
+    // The label for a hash, or the hash in hex if we don't know it.
+    pub fn resolve(&self, hash: u64) -> String {
+        match self.get(hash) {
+            Some(label) => label.to_string(),
+            None => format!("{:#012x}", hash),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
@@ -0,0 +1,26 @@
+// Host-side readers for the files ult_logger writes to the SD card.
+
+pub mod camera;
+pub mod columnar;
+pub mod conversions;
+pub mod csv_export;
+pub mod framedata;
+pub mod hash40;
+pub mod name_tables;
+pub mod names;
+pub mod nav_state;
+pub mod queue;
+pub mod reader;
+pub mod sqlite;
+pub mod stage;
+pub mod stats;
+pub mod record;
+pub mod replay_id;
+pub mod summary;
+pub mod techniques;
+pub mod trace;
+pub mod validate;
+pub mod video_sync;
+
+pub use reader::{ FrameReader, LineReader, LogError, Match };
+pub use record::{ CameraBlock, FighterFrame, Frame, LogEvent, LogLine, MatchHeader };
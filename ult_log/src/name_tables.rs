@@ -0,0 +1,176 @@
+// Name lookups for the host tools: the tables embedded in `names`, plus anything from a names file.
+//
+// A names file is JSON with any of these sections, and its entries take priority over the embedded ones:
+//   {
+//     "fighters": {"84": "buddy"},
+//     "stages": {"3": "Peach's Castle (64)"},
+//     "statuses": {"39": "ATTACK"},
+//     "fighter_statuses": {"7": {"483": "SPECIAL_N_LOOP"}},
+//     "motions": {"0x0c3495ada5": "attack_air_f"}
+//   }
+// Motions can also come from a Hash40 labels file, see `hash40::Labels`.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::hash40::Labels;
+use crate::names;
+use crate::record::FighterFrame;
+
+#[derive(Debug)]
+pub enum NamesError {
+    Io(io::Error),
+    Malformed(String),
+}
+
+impl fmt::Display for NamesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NamesError::Io(e) => write!(f, "couldn't read names file: {}", e),
+            NamesError::Malformed(message) => write!(f, "malformed names file: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for NamesError {}
+
+impl From<io::Error> for NamesError {
+    fn from(e: io::Error) -> Self {
+        NamesError::Io(e)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct NamesFile {
+    fighters: HashMap<String, String>,
+    stages: HashMap<String, String>,
+    statuses: HashMap<String, String>,
+    fighter_statuses: HashMap<String, HashMap<String, String>>,
+    motions: HashMap<String, String>,
+}
+
+fn parse_int(key: &str) -> Option<i64> {
+    match key.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok(),
+        None => key.parse().ok(),
+    }
+}
+
+fn parse_section(section: HashMap<String, String>, what: &str) -> Result<HashMap<i32, String>, NamesError> {
+    section
+        .into_iter()
+        .map(|(key, name)| match parse_int(&key) {
+            Some(value) => Ok((value as i32, name)),
+            None => Err(NamesError::Malformed(format!("{} key {:?} isn't a number", what, key))),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NameTables {
+    fighters: HashMap<i32, String>,
+    stages: HashMap<i32, String>,
+    statuses: HashMap<i32, String>,
+    fighter_statuses: HashMap<(i32, i32), String>,
+    motions: HashMap<u64, String>,
+    labels: Labels,
+}
+
+impl NameTables {
+    // Just the embedded tables.
+    pub fn embedded() -> NameTables {
+        NameTables::default()
+    }
+
+    pub fn from_json(json: &str) -> Result<NameTables, NamesError> {
+        let file: NamesFile = serde_json::from_str(json).map_err(|e| NamesError::Malformed(e.to_string()))?;
+
+        let mut fighter_statuses = HashMap::new();
+        for (fighter_kind, statuses) in file.fighter_statuses {
+            let fighter_kind = parse_int(&fighter_kind).ok_or_else(|| {
+                NamesError::Malformed(format!("fighter_statuses key {:?} isn't a number", fighter_kind))
+            })? as i32;
+            for (status_kind, name) in parse_section(statuses, "fighter_statuses")? {
+                fighter_statuses.insert((fighter_kind, status_kind), name);
+            }
+        }
+
+        let motions = file
+            .motions
+            .into_iter()
+            .map(|(key, name)| match parse_int(&key) {
+                Some(hash) => Ok((hash as u64, name)),
+                None => Err(NamesError::Malformed(format!("motions key {:?} isn't a hash", key))),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(NameTables {
+            fighters: parse_section(file.fighters, "fighters")?,
+            stages: parse_section(file.stages, "stages")?,
+            statuses: parse_section(file.statuses, "statuses")?,
+            fighter_statuses,
+            motions,
+            labels: Labels::default(),
+        })
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<NameTables, NamesError> {
+        NameTables::from_json(&fs::read_to_string(path)?)
+    }
+
+    // Motion names from a labels file, used for anything the names file and embedded tables don't cover.
+    pub fn with_labels(mut self, labels: Labels) -> NameTables {
+        self.labels.extend(labels);
+        self
+    }
+
+    pub fn fighter(&self, fighter_kind: i32) -> Option<&str> {
+        self.fighters.get(&fighter_kind).map(String::as_str).or_else(|| names::fighter_name(fighter_kind))
+    }
+
+    pub fn stage(&self, stage_id: i32) -> Option<&str> {
+        self.stages.get(&stage_id).map(String::as_str).or_else(|| names::stage_name(stage_id))
+    }
+
+    pub fn status(&self, fighter_kind: i32, status_kind: i32) -> Option<&str> {
+        self.fighter_statuses
+            .get(&(fighter_kind, status_kind))
+            .or_else(|| self.statuses.get(&status_kind))
+            .map(String::as_str)
+            .or_else(|| names::status_name(fighter_kind, status_kind))
+    }
+
+    pub fn motion(&self, motion_kind: u64) -> Option<&str> {
+        self.motions
+            .get(&motion_kind)
+            .map(String::as_str)
+            .or_else(|| names::motion_name(motion_kind))
+            .or_else(|| self.labels.get(motion_kind))
+    }
+
+    // Names for a fighter record, with the raw value in place of anything we don't have a name for.
+    pub fn describe(&self, fighter: &FighterFrame) -> FrameNames {
+        FrameNames {
+            fighter: self.fighter(fighter.fighter_name).map(str::to_string).unwrap_or_else(|| fighter.fighter_name.to_string()),
+            status: self
+                .status(fighter.fighter_name, fighter.status_kind)
+                .map(str::to_string)
+                .unwrap_or_else(|| fighter.status_kind.to_string()),
+            motion: self.motion(fighter.motion_kind).map(str::to_string).unwrap_or_else(|| format!("{:#x}", fighter.motion_kind)),
+            stage: self.stage(fighter.stage_id).map(str::to_string).unwrap_or_else(|| fighter.stage_id.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameNames {
+    pub fighter: String,
+    pub status: String,
+    pub motion: String,
+    pub stage: String,
+}
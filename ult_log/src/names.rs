@@ -0,0 +1,522 @@
+// Human-readable names for the raw integers in the log: fighter kinds (`utility::get_kind`), stage IDs
+// (`sv_information::stage_id`), status kinds (`StatusModule::status_kind`) and motion Hash40s
+// (`MotionModule::motion_kind`).
+//
+// This file has no dependencies so the plugin can include it with `#[path]` for in-log names, as well as the
+// host-side tools. The tables cover every playable fighter, the base game's stages plus the DLC stages that are
+// tournament legal, and the common statuses, but only a few motions and no fighter-specific statuses. Anything
+// else comes back as None and can be filled in from a names file on the host side.
+
+// Fighter kinds, in FIGHTER_KIND_* order.
+pub const FIGHTER_NAMES: &[(i32, &str)] = &[
+    (0x00, "mario"),
+    (0x01, "donkey"),
+    (0x02, "link"),
+    (0x03, "samus"),
+    (0x04, "samusd"),
+    (0x05, "yoshi"),
+    (0x06, "kirby"),
+    (0x07, "fox"),
+    (0x08, "pikachu"),
+    (0x09, "luigi"),
+    (0x0a, "ness"),
+    (0x0b, "captain"),
+    (0x0c, "purin"),
+    (0x0d, "peach"),
+    (0x0e, "daisy"),
+    (0x0f, "koopa"),
+    (0x10, "popo"),
+    (0x11, "nana"),
+    (0x12, "sheik"),
+    (0x13, "zelda"),
+    (0x14, "mariod"),
+    (0x15, "pichu"),
+    (0x16, "falco"),
+    (0x17, "marth"),
+    (0x18, "lucina"),
+    (0x19, "younglink"),
+    (0x1a, "ganon"),
+    (0x1b, "mewtwo"),
+    (0x1c, "roy"),
+    (0x1d, "chrom"),
+    (0x1e, "gamewatch"),
+    (0x1f, "metaknight"),
+    (0x20, "pit"),
+    (0x21, "pitb"),
+    (0x22, "szerosuit"),
+    (0x23, "wario"),
+    (0x24, "snake"),
+    (0x25, "ike"),
+    (0x26, "pzenigame"),
+    (0x27, "pfushigisou"),
+    (0x28, "plizardon"),
+    (0x29, "diddy"),
+    (0x2a, "lucas"),
+    (0x2b, "sonic"),
+    (0x2c, "dedede"),
+    (0x2d, "pikmin"),
+    (0x2e, "lucario"),
+    (0x2f, "robot"),
+    (0x30, "toonlink"),
+    (0x31, "wolf"),
+    (0x32, "murabito"),
+    (0x33, "rockman"),
+    (0x34, "wiifit"),
+    (0x35, "rosetta"),
+    (0x36, "littlemac"),
+    (0x37, "gekkouga"),
+    (0x38, "palutena"),
+    (0x39, "pacman"),
+    (0x3a, "reflet"),
+    (0x3b, "shulk"),
+    (0x3c, "koopajr"),
+    (0x3d, "duckhunt"),
+    (0x3e, "ryu"),
+    (0x3f, "ken"),
+    (0x40, "cloud"),
+    (0x41, "kamui"),
+    (0x42, "bayonetta"),
+    (0x43, "inkling"),
+    (0x44, "ridley"),
+    (0x45, "simon"),
+    (0x46, "richter"),
+    (0x47, "krool"),
+    (0x48, "shizue"),
+    (0x49, "gaogaen"),
+    (0x4a, "miifighter"),
+    (0x4b, "miiswordsman"),
+    (0x4c, "miigunner"),
+    (0x51, "packun"),
+    (0x52, "jack"),
+    (0x53, "brave"),
+    (0x54, "buddy"),
+    (0x55, "dolly"),
+    (0x56, "master"),
+    (0x57, "tantan"),
+    (0x58, "pickel"),
+    (0x59, "edge"),
+    (0x5a, "eflame"),
+    (0x5b, "elight"),
+    (0x5c, "demon"),
+    (0x5d, "trail"),
+];
+
+// Stage IDs, in StageID order. Only the standard form of each stage; Battlefield and Omega forms have their own
+// IDs that aren't listed here.
+pub const STAGE_NAMES: &[(i32, &str)] = &[
+    (0x00, "Battlefield"),
+    (0x01, "Big Battlefield"),
+    (0x02, "Final Destination"),
+    (0x03, "Peach's Castle (64)"),
+    (0x04, "Kongo Jungle (64)"),
+    (0x05, "Hyrule Castle"),
+    (0x06, "Super Happy Tree"),
+    (0x07, "Dream Land"),
+    (0x08, "Saffron City"),
+    (0x09, "Mushroom Kingdom"),
+    (0x0a, "Princess Peach's Castle"),
+    (0x0b, "Rainbow Cruise"),
+    (0x0c, "Kongo Falls"),
+    (0x0d, "Jungle Japes"),
+    (0x0e, "Great Bay"),
+    (0x0f, "Temple"),
+    (0x10, "Brinstar"),
+    (0x11, "Yoshi's Island (Melee)"),
+    (0x12, "Yoshi's Story"),
+    (0x13, "Fountain of Dreams"),
+    (0x14, "Green Greens"),
+    (0x15, "Corneria"),
+    (0x16, "Venom"),
+    (0x17, "Pokemon Stadium"),
+    (0x18, "Onett"),
+    (0x19, "Mushroom Kingdom II"),
+    (0x1a, "Brinstar Depths"),
+    (0x1b, "Big Blue"),
+    (0x1c, "Fourside"),
+    (0x1d, "Delfino Plaza"),
+    (0x1e, "Mushroomy Kingdom"),
+    (0x1f, "Figure-8 Circuit"),
+    (0x20, "WarioWare, Inc."),
+    (0x21, "Bridge of Eldin"),
+    (0x22, "Norfair"),
+    (0x23, "Frigate Orpheon"),
+    (0x24, "Yoshi's Island"),
+    (0x25, "Halberd"),
+    (0x26, "Lylat Cruise"),
+    (0x27, "Pokemon Stadium 2"),
+    (0x28, "Port Town Aero Dive"),
+    (0x29, "Castle Siege"),
+    (0x2a, "Distant Planet"),
+    (0x2b, "Smashville"),
+    (0x2c, "New Pork City"),
+    (0x2d, "Summit"),
+    (0x2e, "Skyworld"),
+    (0x2f, "Shadow Moses Island"),
+    (0x30, "Luigi's Mansion"),
+    (0x31, "Pirate Ship"),
+    (0x32, "Spear Pillar"),
+    (0x33, "75 m"),
+    (0x34, "Mario Bros."),
+    (0x35, "Hanenbow"),
+    (0x36, "Green Hill Zone"),
+    (0x37, "3D Land"),
+    (0x38, "Golden Plains"),
+    (0x39, "Paper Mario"),
+    (0x3a, "Gerudo Valley"),
+    (0x3b, "Spirit Train"),
+    (0x3c, "Dream Land GB"),
+    (0x3d, "Unova Pokemon League"),
+    (0x3e, "Prism Tower"),
+    (0x3f, "Mute City SNES"),
+    (0x40, "Magicant"),
+    (0x41, "Arena Ferox"),
+    (0x42, "Reset Bomb Forest"),
+    (0x43, "Tortimer Island"),
+    (0x44, "Balloon Fight"),
+    (0x45, "Living Room"),
+    (0x46, "Find Mii"),
+    (0x47, "Tomodachi Life"),
+    (0x48, "PictoChat 2"),
+    (0x49, "Mushroom Kingdom U"),
+    (0x4a, "Mario Galaxy"),
+    (0x4b, "Mario Circuit"),
+    (0x4c, "Skyloft"),
+    (0x4d, "The Great Cave Offensive"),
+    (0x4e, "Kalos Pokemon League"),
+    (0x4f, "Coliseum"),
+    (0x50, "Flat Zone X"),
+    (0x51, "Palutena's Temple"),
+    (0x52, "Gamer"),
+    (0x53, "Garden of Hope"),
+    (0x54, "Town and City"),
+    (0x55, "Wii Fit Studio"),
+    (0x56, "Boxing Ring"),
+    (0x57, "Gaur Plain"),
+    (0x58, "Duck Hunt"),
+    (0x59, "Wrecking Crew"),
+    (0x5a, "Pilotwings"),
+    (0x5b, "Wuhu Island"),
+    (0x5c, "Windy Hill Zone"),
+    (0x5d, "Wily Castle"),
+    (0x5e, "PAC-LAND"),
+    (0x5f, "Super Mario Maker"),
+    (0x60, "Suzaku Castle"),
+    (0x61, "Midgar"),
+    (0x62, "Umbra Clock Tower"),
+    (0x63, "New Donk City Hall"),
+    (0x64, "Great Plateau Tower"),
+    (0x65, "Moray Towers"),
+    (0x66, "Dracula's Castle"),
+    // DLC stages are numbered after the rest of the stage kinds. Only the tournament legal ones are listed.
+    (0x13c, "Hollow Bastion"),
+    (0x13d, "Small Battlefield"),
+];
+
+// Statuses shared by every fighter, in FIGHTER_STATUS_KIND_* order.
+pub const STATUS_NAMES: &[(i32, &str)] = &[
+    (0x00, "WAIT"),
+    (0x01, "WALK"),
+    (0x02, "WALK_BRAKE"),
+    (0x03, "DASH"),
+    (0x04, "RUN"),
+    (0x05, "RUN_BRAKE"),
+    (0x06, "TURN"),
+    (0x07, "TURN_DASH"),
+    (0x08, "TURN_RUN"),
+    (0x09, "TURN_RUN_BRAKE"),
+    (0x0a, "JUMP_SQUAT"),
+    (0x0b, "JUMP"),
+    (0x0c, "JUMP_AERIAL"),
+    (0x0d, "FLY"),
+    (0x0e, "FALL"),
+    (0x0f, "FALL_AERIAL"),
+    (0x10, "FALL_SPECIAL"),
+    (0x11, "SQUAT"),
+    (0x12, "SQUAT_WAIT"),
+    (0x13, "SQUAT_F"),
+    (0x14, "SQUAT_B"),
+    (0x15, "SQUAT_RV"),
+    (0x16, "LANDING"),
+    (0x17, "LANDING_LIGHT"),
+    (0x18, "LANDING_ATTACK_AIR"),
+    (0x19, "LANDING_FALL_SPECIAL"),
+    (0x1a, "LANDING_DAMAGE_LIGHT"),
+    (0x1b, "GUARD_ON"),
+    (0x1c, "GUARD"),
+    (0x1d, "GUARD_OFF"),
+    (0x1e, "GUARD_DAMAGE"),
+    (0x1f, "ESCAPE"),
+    (0x20, "ESCAPE_F"),
+    (0x21, "ESCAPE_B"),
+    (0x22, "ESCAPE_AIR"),
+    (0x23, "ESCAPE_AIR_SLIDE"),
+    (0x24, "REBOUND_STOP"),
+    (0x25, "REBOUND"),
+    (0x26, "REBOUND_JUMP"),
+    (0x27, "ATTACK"),
+    (0x28, "ATTACK_100"),
+    (0x29, "ATTACK_DASH"),
+    (0x2a, "ATTACK_S3"),
+    (0x2b, "ATTACK_HI3"),
+    (0x2c, "ATTACK_LW3"),
+    (0x2d, "ATTACK_S4_START"),
+    (0x2e, "ATTACK_S4_HOLD"),
+    (0x2f, "ATTACK_S4"),
+    (0x30, "ATTACK_LW4_START"),
+    (0x31, "ATTACK_LW4_HOLD"),
+    (0x32, "ATTACK_LW4"),
+    (0x33, "ATTACK_HI4_START"),
+    (0x34, "ATTACK_HI4_HOLD"),
+    (0x35, "ATTACK_HI4"),
+    (0x36, "ATTACK_AIR"),
+    (0x37, "CATCH"),
+    (0x38, "CATCH_PULL"),
+    (0x39, "CATCH_DASH"),
+    (0x3a, "CATCH_DASH_PULL"),
+    (0x3b, "CATCH_TURN"),
+    (0x3c, "CATCH_WAIT"),
+    (0x3d, "CATCH_ATTACK"),
+    (0x3e, "CATCH_CUT"),
+    (0x3f, "CATCH_JUMP"),
+    (0x40, "THROW"),
+    (0x41, "CAPTURE_PULLED"),
+    (0x42, "CAPTURE_WAIT"),
+    (0x43, "CAPTURE_DAMAGE"),
+    (0x44, "CAPTURE_CUT"),
+    (0x45, "CAPTURE_JUMP"),
+    (0x46, "THROWN"),
+    (0x47, "DAMAGE"),
+    (0x48, "DAMAGE_AIR"),
+    (0x49, "DAMAGE_FLY"),
+    (0x4a, "DAMAGE_FLY_ROLL"),
+    (0x4b, "DAMAGE_FLY_METEOR"),
+    (0x4c, "DAMAGE_FLY_REFLECT_LR"),
+    (0x4d, "DAMAGE_FLY_REFLECT_U"),
+    (0x4e, "DAMAGE_FLY_REFLECT_D"),
+    (0x4f, "DAMAGE_FALL"),
+    (0x50, "DOWN"),
+    (0x51, "DOWN_WAIT"),
+    (0x52, "DOWN_WAIT_CONTINUE"),
+    (0x53, "DOWN_STAND"),
+    (0x54, "DOWN_STAND_FB"),
+    (0x55, "DOWN_STAND_ATTACK"),
+    (0x69, "PASSIVE"),
+    (0x6a, "PASSIVE_FB"),
+    (0x6b, "PASSIVE_WALL"),
+    (0x6c, "PASSIVE_WALL_JUMP"),
+    (0x6d, "PASSIVE_CEIL"),
+    (0x74, "CLIFF_CATCH_MOVE"),
+    (0x75, "CLIFF_CATCH"),
+    (0x76, "CLIFF_WAIT"),
+    (0x77, "CLIFF_ATTACK"),
+    (0x78, "CLIFF_CLIMB"),
+    (0x79, "CLIFF_ESCAPE"),
+    (0x7a, "CLIFF_JUMP1"),
+    (0x7b, "CLIFF_JUMP2"),
+    (0x7c, "CLIFF_JUMP3"),
+    (0x7d, "PASS"),
+    (0xb5, "DEAD"),
+    (0xb6, "REBIRTH"),
+];
+
+// Statuses that only exist for one fighter, as (fighter kind, status kind, name). Fighter-specific statuses
+// are numbered from where the common ones end, so the same number means something different per fighter. None
+// are embedded yet, so they have to come from the `fighter_statuses` section of a names file.
+pub const FIGHTER_STATUS_NAMES: &[(i32, i32, &str)] = &[];
+
+// Common motions, keyed by Hash40 (CRC32 of the name in the low 32 bits, name length in the high 8).
+pub const MOTION_NAMES: &[(u64, &str)] = &[
+    (0x0553c117c3, "wait1"),
+    (0x05cac84679, "wait2"),
+    (0x05bdcf76ef, "wait3"),
+    (0x0523abe34c, "wait4"),
+    (0x09eeaf3544, "walk_slow"),
+    (0x0b4fb275bd, "walk_middle"),
+    (0x09fece0d5d, "walk_fast"),
+    (0x0a9f2abe2f, "walk_brake"),
+    (0x049b6edd00, "dash"),
+    (0x035076a4c0, "run"),
+    (0x092abd2705, "run_brake"),
+    (0x0420201547, "turn"),
+    (0x09e696ecad, "turn_dash"),
+    (0x08afd9b84f, "turn_run"),
+    (0x0ecbdbc635, "turn_run_brake"),
+    (0x0ad160bda8, "jump_squat"),
+    (0x062dd02058, "jump_f"),
+    (0x062abde441, "jump_b"),
+    (0x0d0b71815b, "jump_aerial_f"),
+    (0x0d0c1c4542, "jump_aerial_b"),
+    (0x04fb50df0c, "fall"),
+    (0x0662f2ec10, "fall_f"),
+    (0x06659f2809, "fall_b"),
+    (0x0b29e5172c, "fall_aerial"),
+    (0x0d62c95085, "fall_aerial_f"),
+    (0x0d65a4949c, "fall_aerial_b"),
+    (0x0c016dd1c6, "fall_special"),
+    (0x05d267ef83, "squat"),
+    (0x0aa60712ea, "squat_wait"),
+    (0x08d58dc131, "squat_rv"),
+    (0x0dd934faa1, "landing_light"),
+    (0x0dc81fdc09, "landing_heavy"),
+    (0x1447a7e8cc, "landing_fall_special"),
+    (0x0d562c06c5, "landing_air_n"),
+    (0x0d58f78ef7, "landing_air_f"),
+    (0x0d5f9a4aee, "landing_air_b"),
+    (0x0e5b6e8bce, "landing_air_hi"),
+    (0x0ec50d73a9, "landing_air_lw"),
+    (0x08da2dd4f9, "guard_on"),
+    (0x05af1213cc, "guard"),
+    (0x097ab1c684, "guard_off"),
+    (0x0c692cfb94, "guard_damage"),
+    (0x08b8913c9a, "escape_n"),
+    (0x08b64ab4a8, "escape_f"),
+    (0x08b12770b1, "escape_b"),
+    (0x0ae47033ba, "escape_air"),
+    (0x1060d3285b, "escape_air_slide"),
+    (0x099c52257e, "attack_11"),
+    (0x09055b74c4, "attack_12"),
+    (0x09725c4452, "attack_13"),
+    (0x0a5ae1e07e, "attack_100"),
+    (0x0ea34e5703, "attack_100_end"),
+    (0x0b6c751ced, "attack_dash"),
+    (0x0b7dd72b57, "attack_s3_s"),
+    (0x0cba28ee97, "attack_s3_hi"),
+    (0x0c244b16f0, "attack_s3_lw"),
+    (0x0acbfc42e6, "attack_hi3"),
+    (0x0a18b4d5e5, "attack_lw3"),
+    (0x0f81c5195c, "attack_s4_start"),
+    (0x0ea274b695, "attack_s4_hold"),
+    (0x0b78983dd2, "attack_s4_s"),
+    (0x0c27ffd62e, "attack_s4_hi"),
+    (0x0cb99c2e49, "attack_s4_lw"),
+    (0x106382a495, "attack_hi4_start"),
+    (0x0f5db36b5c, "attack_hi4_hold"),
+    (0x0a5598d745, "attack_hi4"),
+    (0x10575fd680, "attack_lw4_start"),
+    (0x0fee188235, "attack_lw4_hold"),
+    (0x0a86d04046, "attack_lw4"),
+    (0x0c3a4e2597, "attack_air_n"),
+    (0x0c3495ada5, "attack_air_f"),
+    (0x0c33f869bc, "attack_air_b"),
+    (0x0dde67d935, "attack_air_hi"),
+    (0x0d40042152, "attack_air_lw"),
+    (0x05c56b7b64, "catch"),
+    (0x0ae4c85cc1, "catch_dash"),
+    (0x0a5f869486, "catch_turn"),
+    (0x0a02480224, "catch_wait"),
+    (0x0c0f9e0724, "catch_attack"),
+    (0x097c7ceeb1, "catch_cut"),
+    (0x0749f709a8, "throw_f"),
+    (0x074e9acdb1, "throw_b"),
+    (0x08a0abc72c, "throw_hi"),
+    (0x083ec83f4b, "throw_lw"),
+    (0x11b11f4cb6, "capture_pulled_hi"),
+    (0x0f8b734bcb, "capture_wait_hi"),
+    (0x1160574c75, "capture_damage_hi"),
+    (0x0885baf52e, "thrown_f"),
+    (0x0882d73137, "thrown_b"),
+    (0x09a4bcacc5, "thrown_hi"),
+    (0x093adf54a2, "thrown_lw"),
+    (0x0915c5de42, "special_n"),
+    (0x0976c3b29b, "special_s"),
+    (0x0a28f17495, "special_hi"),
+    (0x0ab6928cf2, "special_lw"),
+    (0x0d483c0ed2, "special_air_n"),
+    (0x0d2b3a620b, "special_air_s"),
+    (0x0ed8a31e01, "special_air_hi"),
+    (0x0e46c0e666, "special_air_lw"),
+    (0x0b7dc027b4, "damage_hi_1"),
+    (0x0be4c9760e, "damage_hi_2"),
+    (0x0b93ce4698, "damage_hi_3"),
+    (0x0aef48d400, "damage_n_1"),
+    (0x0a764185ba, "damage_n_2"),
+    (0x0a0146b52c, "damage_n_3"),
+    (0x0be41a3e99, "damage_lw_1"),
+    (0x0b7d136f23, "damage_lw_2"),
+    (0x0b0a145fb5, "damage_lw_3"),
+    (0x0c8956a9ee, "damage_air_1"),
+    (0x0c105ff854, "damage_air_2"),
+    (0x0c6758c8c2, "damage_air_3"),
+    (0x0dc8ca7173, "damage_fly_hi"),
+    (0x0cfb292648, "damage_fly_n"),
+    (0x0d56a98914, "damage_fly_lw"),
+    (0x0e08a589d4, "damage_fly_top"),
+    (0x0fd4ac733b, "damage_fly_roll"),
+    (0x11ca458285, "damage_fly_meteor"),
+    (0x0b72bd4946, "damage_fall"),
+    (0x0cc24d7e43, "down_bound_u"),
+    (0x0ca8fd5eb1, "down_bound_d"),
+    (0x0b95b751c9, "down_wait_u"),
+    (0x0bff07713b, "down_wait_d"),
+    (0x0cff952a23, "down_stand_u"),
+    (0x0c95250ad1, "down_stand_d"),
+    (0x0e0bb25962, "down_forward_u"),
+    (0x0e61027990, "down_forward_d"),
+    (0x0bbe6bc6c8, "down_back_u"),
+    (0x0bd4dbe63a, "down_back_d"),
+    (0x0d6efe0514, "down_attack_u"),
+    (0x0d044e25e6, "down_attack_d"),
+    (0x0db2dd5117, "down_damage_u"),
+    (0x0dd86d71e5, "down_damage_d"),
+    (0x0776effdcf, "passive"),
+    (0x0f3a3f311f, "passive_stand_f"),
+    (0x0f3d52f506, "passive_stand_b"),
+    (0x0c8a426770, "passive_wall"),
+    (0x1117c77017, "passive_wall_jump"),
+    (0x0c2f47fb21, "passive_ceil"),
+    (0x0b99d9746c, "cliff_catch"),
+    (0x0a7c8b8f5e, "cliff_wait"),
+    (0x11c48e6de3, "cliff_climb_quick"),
+    (0x101533083b, "cliff_climb_slow"),
+    (0x127aee8c0f, "cliff_attack_quick"),
+    (0x117ec2f449, "cliff_attack_slow"),
+    (0x1237aa75f7, "cliff_escape_quick"),
+    (0x11885a5429, "cliff_escape_slow"),
+    (0x118b87d5b5, "cliff_jump_quick1"),
+    (0x11128e840f, "cliff_jump_quick2"),
+    (0x10c04b7735, "cliff_jump_slow1"),
+    (0x105942268f, "cliff_jump_slow2"),
+    (0x0844853330, "furafura"),
+    (0x10708f1e7c, "furafura_start_u"),
+    (0x0cacd460a4, "furafura_end"),
+    (0x10743e4673, "shield_break_fly"),
+    (0x137b0674ad, "shield_break_down_u"),
+    (0x0bb634be24, "appeal_hi_r"),
+    (0x0b4c3b8347, "appeal_hi_l"),
+    (0x0a8149e8fa, "appeal_s_r"),
+    (0x0a7b46d599, "appeal_s_l"),
+    (0x0b2feea709, "appeal_lw_r"),
+    (0x0bd5e19a6a, "appeal_lw_l"),
+    (0x076370fe5a, "entry_r"),
+    (0x07997fc339, "entry_l"),
+    (0x074431f597, "rebirth"),
+    (0x05e54ad4c0, "win_1"),
+    (0x057c43857a, "win_2"),
+    (0x050b44b5ec, "win_3"),
+    (0x042af5a458, "lose"),
+];
+
+fn lookup<K: PartialEq + Copy>(table: &[(K, &'static str)], key: K) -> Option<&'static str> {
+    table.iter().find(|(k, _)| *k == key).map(|(_, name)| *name)
+}
+
+pub fn fighter_name(fighter_kind: i32) -> Option<&'static str> {
+    lookup(FIGHTER_NAMES, fighter_kind)
+}
+
+pub fn stage_name(stage_id: i32) -> Option<&'static str> {
+    lookup(STAGE_NAMES, stage_id)
+}
+
+// Fighter-specific statuses take priority, since they overlap with each other rather than the common ones.
+pub fn status_name(fighter_kind: i32, status_kind: i32) -> Option<&'static str> {
+    FIGHTER_STATUS_NAMES
+        .iter()
+        .find(|(kind, status, _)| *kind == fighter_kind && *status == status_kind)
+        .map(|(_, _, name)| *name)
+        .or_else(|| lookup(STATUS_NAMES, status_kind))
+}
+
+pub fn motion_name(motion_kind: u64) -> Option<&'static str> {
+    lookup(MOTION_NAMES, motion_kind)
+}
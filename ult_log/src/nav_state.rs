@@ -0,0 +1,208 @@
+// The navigation state machine: which state each MBST label moves us to, and what else has to happen when it
+// does. The plugin's label hook feeds every label through `next_nav`.
+//
+// Like `queue`, this file only depends on other shared files so the plugin can include it with `#[path]`, which
+// lets label traces from the SD card be replayed against it on the host (see `trace::replay_fixture`).
+
+use crate::queue::ReplayOutcome;
+
+// Track where we are in the navigation sequence
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum CurrentNavigation {
+    // Starts with Main Menu Navigation
+    MainWaitingForLoad, // Loading
+    MainOnMelee, // Pressing Down
+    MainOnSpirits, // Pressing Right
+    MainOnOnline, // Pressing A
+    MainWaitingOnline, // Waiting
+    MainInOnline, // Pressing Down
+    MainOnSharedContent, // Pressing A
+    // Moves to Shared Content Navigation
+    ScWaitingForLoad, // Pressing X (for 10 seconds for load times)
+    ScSearchSubmenuTop, // Press Up Here (press once)
+    ScSearchSubmenuBottom, // Pressing A (until keyboard shows up)
+    ScKeyboard, // Should be automated - unused
+    ScSearchResults, // See if "No content found." is an MBST, use to determine if bad ID 
+        // - mnu_share_search_result_title -> HoverReplay
+    _ScBadId, // Press B Here (Once, after waiting 3 seconds), then wait 3 seconds, then we're at ScSearchSubmenuBottom - unused
+    ScHoverReplay, // Pressing A, until Loading MBST or game start
+        // mnu_share_detail_stage, pop_online_communicating_mini once clicked again and we're loading - use that to go to waiting for game
+    ScWaitingForGame, // Holding X, until GO
+    ScGO, // Press X+Down to hide overlay,
+    ScPlayback, // Pressing B, until we scene transition back to previous scene
+    // Move 
+    DoneHoverPlay, // Pressing B until we're back at the Search Menu, then we're at ScSearchSubmenuBottom - unused
+    ScIncompatible, // Replay is from another game version, pressing B to close the popup and back out
+    ScNoResult, // Search came back with no content or region locked content, pressing B to close the popup
+    ScVideo, // We only work with replays, so going back with B from videos, screenshots and stages.
+    Complete, // When we've gone through all the replays
+    // Daemon mode
+    DaemonBackingOut, // Pressing B until we're back on the main menu
+    DaemonWaiting, // Idle on the main menu until new replay IDs show up in the manifest
+    // Watchdog recovery
+    RecoverToSubmenu, // Pressing B until we're back at the Search Menu
+    RecoverToMain, // Pressing B until we're back on a main or online menu we recognize
+}
+
+impl CurrentNavigation {
+    pub const ALL: &'static [CurrentNavigation] = &[
+        CurrentNavigation::MainWaitingForLoad,
+        CurrentNavigation::MainOnMelee,
+        CurrentNavigation::MainOnSpirits,
+        CurrentNavigation::MainOnOnline,
+        CurrentNavigation::MainWaitingOnline,
+        CurrentNavigation::MainInOnline,
+        CurrentNavigation::MainOnSharedContent,
+        CurrentNavigation::ScWaitingForLoad,
+        CurrentNavigation::ScSearchSubmenuTop,
+        CurrentNavigation::ScSearchSubmenuBottom,
+        CurrentNavigation::ScKeyboard,
+        CurrentNavigation::ScSearchResults,
+        CurrentNavigation::_ScBadId,
+        CurrentNavigation::ScHoverReplay,
+        CurrentNavigation::ScWaitingForGame,
+        CurrentNavigation::ScGO,
+        CurrentNavigation::ScPlayback,
+        CurrentNavigation::DoneHoverPlay,
+        CurrentNavigation::ScIncompatible,
+        CurrentNavigation::ScNoResult,
+        CurrentNavigation::ScVideo,
+        CurrentNavigation::Complete,
+        CurrentNavigation::DaemonBackingOut,
+        CurrentNavigation::DaemonWaiting,
+        CurrentNavigation::RecoverToSubmenu,
+        CurrentNavigation::RecoverToMain,
+    ];
+
+    // The state with this Debug name, as written to label traces.
+    pub fn from_name(name: &str) -> Option<CurrentNavigation> {
+        CurrentNavigation::ALL.iter().copied().find(|nav| format!("{:?}", nav) == name)
+    }
+}
+
+// Something the label hook has to do besides changing state.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum NavAction {
+    ConfirmSearch, // The search we typed came back
+    MarkOutcome(ReplayOutcome), // We know what the current ID was
+    ReplayDone, // Back on the search menu after a replay
+    SetDaemonResume(CurrentNavigation), // Where to pick back up after waiting in daemon mode
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct NavTransition {
+    pub nav: CurrentNavigation,
+    // Run in order, after moving to `nav`.
+    pub actions: Vec<NavAction>,
+    pub messages: Vec<&'static str>,
+}
+
+// Detail screens for Shared Content that isn't a replay.
+fn non_replay_outcome(label: &str) -> Option<ReplayOutcome> {
+    match label {
+        "mnu_share_detail_movie_title" => Some(ReplayOutcome::Video),
+        "mnu_share_detail_photo_title" => Some(ReplayOutcome::Screenshot),
+        "mnu_share_detail_stage_edit_title" => Some(ReplayOutcome::Stage),
+        _ => None,
+    }
+}
+
+// Where `label` takes us from `nav`. `ready_go` is only asked on the in-game label we wait for GO! on.
+pub fn next_nav(label: &str, nav: CurrentNavigation, ready_go: impl Fn() -> bool) -> NavTransition {
+    let mut transition = NavTransition { nav, actions: vec![], messages: vec![] };
+    let t = &mut transition;
+    let go = |t: &mut NavTransition, next: CurrentNavigation, message: &'static str| {
+        t.nav = next;
+        t.messages.push(message);
+    };
+
+    if let Some(replay_outcome) = non_replay_outcome(label) {
+        if t.nav != CurrentNavigation::ScVideo {
+            go(t, CurrentNavigation::ScVideo, "We are looking at something that isn't a replay. Skipping!");
+            t.actions.push(NavAction::ConfirmSearch);
+            t.actions.push(NavAction::MarkOutcome(replay_outcome));
+        }
+    }
+
+    if label == "mnu_top_help_melee" && t.nav == CurrentNavigation::MainWaitingForLoad {
+        go(t, CurrentNavigation::MainOnMelee, "Melee selected - should move down!");
+    }
+    if label == "mnu_top_help_spirits" && t.nav == CurrentNavigation::MainOnMelee {
+        go(t, CurrentNavigation::MainOnSpirits, "Spirits selected - should move right!");
+    }
+    if label == "mnu_top_help_online" && t.nav == CurrentNavigation::MainOnSpirits {
+        go(t, CurrentNavigation::MainOnOnline, "Online selected - should enter!");
+    }
+    if label == "pop_online_connecting" && t.nav == CurrentNavigation::MainOnOnline {
+        go(t, CurrentNavigation::MainWaitingOnline, "In a cancel pop-up - don't press anything!");
+    }
+    if label == "mnu_onl_top_help_onl_melee" && (t.nav == CurrentNavigation::MainOnOnline || t.nav == CurrentNavigation::MainWaitingOnline) {
+        go(t, CurrentNavigation::MainInOnline, "Online entered - should move down!");
+    }
+    if label == "mnu_onl_top_help_contribution" && t.nav == CurrentNavigation::MainInOnline {
+        go(t, CurrentNavigation::MainOnSharedContent, "SC selected - should enter!");
+    }
+    if label == "mnu_share_cat_movie" && t.nav == CurrentNavigation::ScWaitingForLoad {
+        go(t, CurrentNavigation::ScSearchSubmenuTop, "In SubMenu!");
+    }
+    if label == "mnu_share_search_result_title" && t.nav == CurrentNavigation::ScSearchResults {
+        go(t, CurrentNavigation::ScHoverReplay, "In Search Results!");
+        t.actions.push(NavAction::ConfirmSearch);
+    }
+    // The popup's label is fetched on every frame it's open, so only act on it the first time.
+    if label == "pop_share_no_post" && t.nav != CurrentNavigation::ScNoResult {
+        go(t, CurrentNavigation::ScNoResult, "Bad ID!");
+        t.actions.push(NavAction::ConfirmSearch);
+        t.actions.push(NavAction::MarkOutcome(ReplayOutcome::NotFound));
+    }
+    if label == "pop_share_region_error" && t.nav != CurrentNavigation::ScNoResult {
+        go(t, CurrentNavigation::ScNoResult, "Content is not available in this region!");
+        t.actions.push(NavAction::ConfirmSearch);
+        t.actions.push(NavAction::MarkOutcome(ReplayOutcome::RegionMismatch));
+    }
+    if label == "pop_share_replay_version_error" && t.nav != CurrentNavigation::ScIncompatible {
+        // Shows up after trying to play a replay saved on an older version of the game.
+        go(t, CurrentNavigation::ScIncompatible, "Replay is from a different game version!");
+        t.actions.push(NavAction::MarkOutcome(ReplayOutcome::Incompatible));
+    }
+    if label == "pop_online_communicating_mini" && t.nav == CurrentNavigation::ScHoverReplay {
+        go(t, CurrentNavigation::ScWaitingForGame, "Game Starting!");
+    }
+    if label == "mel_pause_filter_00" && t.nav == CurrentNavigation::ScWaitingForGame && ready_go() {
+        go(t, CurrentNavigation::ScGO, "GO!");
+    }
+    if label == "mnu_share_cat_movie" && (t.nav == CurrentNavigation::DoneHoverPlay || t.nav == CurrentNavigation::ScPlayback || t.nav == CurrentNavigation::ScVideo) {
+        // After we've either played a replay (or skipped a video) we're back at the Search menu on "Enter ID" at the bottom.
+        go(t, CurrentNavigation::ScSearchSubmenuBottom, "Returned to SubMenu!");
+        t.actions.push(NavAction::ReplayDone);
+    }
+    if label == "mnu_share_cat_movie" && t.nav == CurrentNavigation::ScIncompatible {
+        go(t, CurrentNavigation::ScSearchSubmenuBottom, "Skipped incompatible replay, returned to SubMenu!");
+    }
+    if label == "mnu_share_cat_movie" && t.nav == CurrentNavigation::ScNoResult {
+        go(t, CurrentNavigation::ScSearchSubmenuBottom, "Closed search popup, returned to SubMenu!");
+    }
+    if label == "mnu_share_cat_movie" && t.nav == CurrentNavigation::RecoverToSubmenu {
+        go(t, CurrentNavigation::ScSearchSubmenuBottom, "Recovered to SubMenu!");
+    }
+    if t.nav == CurrentNavigation::RecoverToMain {
+        // Pick back up from whichever menu we landed on while backing out.
+        if label == "mnu_top_help_melee" {
+            go(t, CurrentNavigation::MainOnMelee, "Recovered to Main Menu!");
+        } else if label == "mnu_top_help_online" {
+            go(t, CurrentNavigation::MainOnOnline, "Recovered to Main Menu on Online!");
+        } else if label == "mnu_onl_top_help_contribution" {
+            go(t, CurrentNavigation::MainOnSharedContent, "Recovered to Online Menu on SC!");
+        }
+    }
+    if t.nav == CurrentNavigation::DaemonBackingOut {
+        if label == "mnu_top_help_online" {
+            go(t, CurrentNavigation::DaemonWaiting, "Back on Main Menu, waiting for replays!");
+            t.actions.push(NavAction::SetDaemonResume(CurrentNavigation::MainOnOnline));
+        } else if label == "mnu_top_help_melee" {
+            go(t, CurrentNavigation::DaemonWaiting, "Back on Main Menu, waiting for replays!");
+            t.actions.push(NavAction::SetDaemonResume(CurrentNavigation::MainOnMelee));
+        }
+    }
+    transition
+}
@@ -0,0 +1,203 @@
+// The plugin's queue of replay IDs to search for and record, and what happened to each.
+//
+// Like `names`, this file has no dependencies beyond `replay_id` (shared the same way) so the plugin can include
+// it with `#[path]`, which lets the queue be tested on the host. IDs are parsed before they're queued.
+
+use crate::replay_id::ReplayId;
+
+// What we found when we searched for a replay ID.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReplayOutcome {
+    Recorded, // A replay that we watched and wrote a log for
+    Video, // A Shared Content video, which we can't record
+    Screenshot, // A Shared Content screenshot
+    Stage, // A Shared Content custom stage
+    NotFound, // No content found for the ID
+    RegionMismatch, // Content that can't be viewed from this region
+    Incompatible, // A replay recorded on a different game version, which can't be played
+    InvalidId, // Not a well-formed replay ID, so it was never typed
+    FailedAfterRetries(u32), // Failed to load or record on every attempt, with the number of retries
+}
+
+impl ReplayOutcome {
+    pub fn name(&self) -> String {
+        match self {
+            ReplayOutcome::Recorded => "recorded".to_string(),
+            ReplayOutcome::Video => "video".to_string(),
+            ReplayOutcome::Screenshot => "screenshot".to_string(),
+            ReplayOutcome::Stage => "stage".to_string(),
+            ReplayOutcome::NotFound => "not_found".to_string(),
+            ReplayOutcome::RegionMismatch => "region_mismatch".to_string(),
+            ReplayOutcome::Incompatible => "incompatible".to_string(),
+            ReplayOutcome::InvalidId => "invalid_id".to_string(),
+            ReplayOutcome::FailedAfterRetries(retries) => format!("failed_after_{}_retries", retries),
+        }
+    }
+}
+
+// The list of replay IDs we're working through. The current ID stays current until it has an outcome, so a
+// replay that fails can be retried, and the queue only finishes once the last ID has been dealt with.
+pub struct ReplayQueue {
+    ids: Vec<ReplayId>,
+    position: usize,
+    outcomes: Vec<Option<ReplayOutcome>>,
+    attempts: Vec<u32>,
+    // Set between a search for the current ID going through and it getting an outcome.
+    in_flight: bool,
+}
+
+impl ReplayQueue {
+    pub fn new(ids: Vec<ReplayId>) -> ReplayQueue {
+        let len = ids.len();
+        ReplayQueue {
+            ids,
+            position: 0,
+            outcomes: vec![None; len],
+            attempts: vec![0; len],
+            in_flight: false,
+        }
+    }
+
+    // The ID we're currently working on, or None once every ID has an outcome.
+    pub fn current(&self) -> Option<ReplayId> {
+        self.ids.get(self.position).copied()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.ids.len()
+    }
+
+    pub fn in_flight(&self) -> bool {
+        self.in_flight
+    }
+
+    // How many times we've searched for the current ID.
+    pub fn attempts(&self) -> u32 {
+        self.attempts.get(self.position).copied().unwrap_or(0)
+    }
+
+    // Add IDs to the end of the queue, e.g. ones appended to the manifest since we last read it.
+    pub fn append(&mut self, ids: Vec<ReplayId>) {
+        for id in ids {
+            self.ids.push(id);
+            self.outcomes.push(None);
+            self.attempts.push(0);
+        }
+    }
+
+    // How many IDs were recorded, out of how many in the queue.
+    pub fn tally(&self) -> (usize, usize) {
+        let recorded = self.outcomes
+            .iter()
+            .filter(|replay_outcome| **replay_outcome == Some(ReplayOutcome::Recorded))
+            .count();
+        (recorded, self.ids.len())
+    }
+
+    // Called when a search for the current ID goes through.
+    pub fn start_attempt(&mut self) {
+        if let Some(attempts) = self.attempts.get_mut(self.position) {
+            *attempts += 1;
+            self.in_flight = true;
+        }
+    }
+
+    // Give up on the current attempt without an outcome, so the same ID comes up again.
+    pub fn abandon_attempt(&mut self) {
+        self.in_flight = false;
+    }
+
+    // Record what happened to the current ID and move on to the next one.
+    pub fn mark_outcome(&mut self, replay_outcome: ReplayOutcome) {
+        if let Some(slot) = self.outcomes.get_mut(self.position) {
+            *slot = Some(replay_outcome);
+            self.advance();
+        }
+    }
+
+    pub fn advance(&mut self) {
+        self.in_flight = false;
+        if self.position < self.ids.len() {
+            self.position += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(raw: &str) -> ReplayId {
+        ReplayId::parse(raw).unwrap()
+    }
+
+    fn queue(ids: &[&str]) -> ReplayQueue {
+        ReplayQueue::new(ids.iter().map(|raw| id(raw)).collect())
+    }
+
+    #[test]
+    fn empty_queue_is_finished() {
+        let queue = queue(&[]);
+        assert!(queue.is_finished());
+        assert_eq!(queue.current(), None);
+        assert_eq!(queue.attempts(), 0);
+        assert_eq!(queue.tally(), (0, 0));
+    }
+
+    #[test]
+    fn outcome_on_last_id_finishes() {
+        let mut queue = queue(&["ABCD1234", "EFGH5678"]);
+        queue.start_attempt();
+        queue.mark_outcome(ReplayOutcome::Recorded);
+        assert_eq!(queue.current(), Some(id("EFGH5678")));
+        queue.start_attempt();
+        queue.mark_outcome(ReplayOutcome::NotFound);
+        assert!(queue.is_finished());
+        assert!(!queue.in_flight());
+        assert_eq!(queue.current(), None);
+        assert_eq!(queue.tally(), (1, 2));
+    }
+
+    #[test]
+    fn outcome_and_advance_after_end_do_nothing() {
+        let mut queue = queue(&["ABCD1234"]);
+        queue.mark_outcome(ReplayOutcome::Recorded);
+        queue.mark_outcome(ReplayOutcome::Video);
+        queue.advance();
+        queue.start_attempt();
+        assert!(queue.is_finished());
+        assert!(!queue.in_flight());
+        assert_eq!(queue.attempts(), 0);
+        assert_eq!(queue.tally(), (1, 1));
+    }
+
+    #[test]
+    fn retry_keeps_the_same_id_and_counts_attempts() {
+        let mut queue = queue(&["ABCD1234", "EFGH5678"]);
+        queue.start_attempt();
+        assert!(queue.in_flight());
+        queue.abandon_attempt();
+        assert!(!queue.in_flight());
+        assert_eq!(queue.current(), Some(id("ABCD1234")));
+        assert_eq!(queue.attempts(), 1);
+
+        queue.start_attempt();
+        assert_eq!(queue.attempts(), 2);
+        queue.mark_outcome(ReplayOutcome::FailedAfterRetries(1));
+        assert_eq!(queue.current(), Some(id("EFGH5678")));
+        assert_eq!(queue.attempts(), 0);
+    }
+
+    #[test]
+    fn append_after_finishing_resumes() {
+        let mut queue = queue(&["ABCD1234"]);
+        queue.mark_outcome(ReplayOutcome::Recorded);
+        assert!(queue.is_finished());
+
+        queue.append(vec![id("EFGH5678")]);
+        assert!(!queue.is_finished());
+        assert_eq!(queue.current(), Some(id("EFGH5678")));
+        assert_eq!(queue.attempts(), 0);
+        assert_eq!(queue.tally(), (1, 2));
+    }
+}
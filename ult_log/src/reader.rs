@@ -0,0 +1,296 @@
+// Streaming reader for the plugin's JSON-lines match logs.
+
+use serde_json::Value;
+use std::fmt;
+use std::fs::File;
+use std::io::{ self, BufRead, BufReader };
+use std::path::Path;
+
+use crate::record::{ FighterFrame, FighterInfo, Frame, LogEvent, LogLine, MatchHeader };
+
+#[derive(Debug)]
+pub enum LogError {
+    Io(io::Error),
+    Malformed { line: usize, message: String },
+    // The last line of the file was cut off, usually because the game crashed while writing it.
+    Truncated { line: usize },
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogError::Io(e) => write!(f, "couldn't read log: {}", e),
+            LogError::Malformed { line, message } => write!(f, "malformed log line {}: {}", line, message),
+            LogError::Truncated { line } => write!(f, "log was truncated at line {}", line),
+        }
+    }
+}
+
+impl std::error::Error for LogError {}
+
+impl From<io::Error> for LogError {
+    fn from(e: io::Error) -> Self {
+        LogError::Io(e)
+    }
+}
+
+fn parse_line(line: &str, line_number: usize) -> Result<LogLine, LogError> {
+    let malformed = |message: String| LogError::Malformed { line: line_number, message };
+
+    let value: Value = serde_json::from_str(line).map_err(|e| malformed(e.to_string()))?;
+    if value.get("event").is_some() {
+        let event: LogEvent = serde_json::from_value(value).map_err(|e| malformed(e.to_string()))?;
+        return Ok(LogLine::Event(event));
+    }
+    let fighter: FighterFrame = serde_json::from_value(value).map_err(|e| malformed(e.to_string()))?;
+    Ok(LogLine::Fighter(Box::new(fighter)))
+}
+
+// Iterates over the lines of a log one at a time. Blank lines are skipped, and a bad line is returned as an
+// error without stopping the iteration.
+pub struct LineReader<R: BufRead> {
+    reader: R,
+    line_number: usize,
+    buffer: String,
+}
+
+impl<R: BufRead> LineReader<R> {
+    pub fn new(reader: R) -> LineReader<R> {
+        LineReader { reader, line_number: 0, buffer: String::new() }
+    }
+}
+
+impl<R: BufRead> Iterator for LineReader<R> {
+    type Item = Result<LogLine, LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.clear();
+            match self.reader.read_line(&mut self.buffer) {
+                Err(e) => return Some(Err(LogError::Io(e))),
+                Ok(0) => return None,
+                Ok(_) => {}
+            }
+            self.line_number += 1;
+
+            let complete = self.buffer.ends_with('\n');
+            let line = self.buffer.trim();
+            if line.is_empty() {
+                continue;
+            }
+            return Some(match parse_line(line, self.line_number) {
+                // Only the last line of a file can be missing its newline.
+                Err(LogError::Malformed { .. }) if !complete => Err(LogError::Truncated { line: self.line_number }),
+                result => result,
+            });
+        }
+    }
+}
+
+// Groups the lines of a log into frames. A new frame starts whenever the frame timer changes or a fighter shows
+// up twice.
+pub struct FrameReader<R: BufRead> {
+    lines: LineReader<R>,
+    pending: Option<Frame>,
+    next_index: usize,
+    errors: Vec<LogError>,
+}
+
+impl<R: BufRead> FrameReader<R> {
+    pub fn new(reader: R) -> FrameReader<R> {
+        FrameReader { lines: LineReader::new(reader), pending: None, next_index: 0, errors: Vec::new() }
+    }
+
+    // Lines that couldn't be parsed so far. They're skipped rather than ending the iteration.
+    pub fn errors(&self) -> &[LogError] {
+        &self.errors
+    }
+
+    pub fn into_errors(self) -> Vec<LogError> {
+        self.errors
+    }
+}
+
+fn new_frame(next_index: &mut usize, num_frames_left: u32) -> Frame {
+    let frame = Frame { index: *next_index, num_frames_left, ..Frame::default() };
+    *next_index += 1;
+    frame
+}
+
+impl<R: BufRead> Iterator for FrameReader<R> {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        for line in self.lines.by_ref() {
+            match line {
+                Err(e) => self.errors.push(e),
+                Ok(LogLine::Event(event)) => {
+                    if self.pending.is_none() {
+                        self.pending = Some(new_frame(&mut self.next_index, 0));
+                    }
+                    if let Some(frame) = self.pending.as_mut() {
+                        frame.events.push(event);
+                    }
+                }
+                Ok(LogLine::Fighter(fighter)) => {
+                    let starts_new_frame = match &self.pending {
+                        None => true,
+                        Some(frame) => {
+                            !frame.fighters.is_empty()
+                                && (frame.num_frames_left != fighter.num_frames_left
+                                    || frame.fighter(fighter.fighter_id).is_some())
+                        }
+                    };
+                    if starts_new_frame {
+                        let frame = new_frame(&mut self.next_index, fighter.num_frames_left);
+                        let finished = self.pending.replace(frame);
+                        if let Some(frame) = self.pending.as_mut() {
+                            frame.fighters.push(*fighter);
+                        }
+                        if finished.is_some() {
+                            return finished;
+                        }
+                    } else if let Some(frame) = self.pending.as_mut() {
+                        frame.num_frames_left = fighter.num_frames_left;
+                        frame.fighters.push(*fighter);
+                    }
+                }
+            }
+        }
+        self.pending.take()
+    }
+}
+
+// A whole match log read into memory.
+#[derive(Debug, Default)]
+pub struct Match {
+    pub header: MatchHeader,
+    pub frames: Vec<Frame>,
+    // Lines that couldn't be parsed, including a truncated final line.
+    pub errors: Vec<LogError>,
+}
+
+impl Match {
+    pub fn from_reader<R: BufRead>(reader: R, mut header: MatchHeader) -> Match {
+        let mut frames_reader = FrameReader::new(reader);
+        let frames: Vec<Frame> = frames_reader.by_ref().collect();
+        let errors = frames_reader.into_errors();
+
+        if let Some(first) = frames.iter().find(|frame| !frame.fighters.is_empty()) {
+            header.stage_id = Some(first.fighters[0].stage_id);
+            header.fighters = first
+                .fighters
+                .iter()
+                .map(|fighter| FighterInfo { fighter_id: fighter.fighter_id, fighter_name: fighter.fighter_name })
+                .collect();
+        }
+        Match { header, frames, errors }
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Match, LogError> {
+        let path = path.as_ref();
+        let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+        let header = MatchHeader::from_file_name(file_name);
+        Ok(Match::from_reader(BufReader::new(File::open(path)?), header))
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        self.errors.iter().any(|e| matches!(e, LogError::Truncated { .. }))
+    }
+
+    pub fn malformed_lines(&self) -> usize {
+        self.errors.iter().filter(|e| matches!(e, LogError::Malformed { .. })).count()
+    }
+
+    pub fn events(&self) -> impl Iterator<Item = &LogEvent> {
+        self.frames.iter().flat_map(|frame| frame.events.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn fighter_line(num_frames_left: u32, fighter_id: i32) -> String {
+        format!(
+            r#"{{"num_frames_left":{},"fighter_id":{},"fighter_name":1,"stock_count":3,"status_kind":0,"motion_kind":0,"damage":0.0,"shield_size":50.0,"facing":1.0,"pos_x":0.0,"pos_y":0.0,"hitstun_left":0.0,"attack_connected":false,"animation_frame_num":0.0,"can_act":true,"stage_id":0}}"#,
+            num_frames_left, fighter_id
+        )
+    }
+
+    fn event_line(event: &str) -> String {
+        format!(r#"{{"event":"{}","timestamp_us":1}}"#, event)
+    }
+
+    fn frames(log: &str) -> (Vec<Frame>, Vec<LogError>) {
+        let mut reader = FrameReader::new(Cursor::new(log.to_string()));
+        let frames: Vec<Frame> = reader.by_ref().collect();
+        (frames, reader.into_errors())
+    }
+
+    #[test]
+    fn skips_a_bad_line_and_keeps_reading() {
+        let log = format!("{}\n\n{{\"num_frames_left\":\n{}\n", fighter_line(0, 0), fighter_line(0, 1));
+        let lines: Vec<Result<LogLine, LogError>> = LineReader::new(Cursor::new(log)).collect();
+        assert_eq!(lines.len(), 3);
+        assert!(matches!(lines[0], Ok(LogLine::Fighter(_))));
+        assert!(matches!(lines[1], Err(LogError::Malformed { line: 3, .. })));
+        assert!(matches!(lines[2], Ok(LogLine::Fighter(_))));
+    }
+
+    #[test]
+    fn reports_a_cut_off_last_line_as_truncated() {
+        let log = format!("{}\n{}\n{{\"num_frames_left\":0,\"figh", fighter_line(0, 0), fighter_line(0, 1));
+        let log = Match::from_reader(Cursor::new(log), MatchHeader::default());
+        assert_eq!(log.frames.len(), 1);
+        assert!(log.is_truncated());
+        assert_eq!(log.malformed_lines(), 0);
+        assert!(matches!(log.errors[0], LogError::Truncated { line: 3 }));
+    }
+
+    #[test]
+    fn splits_untimed_frames_when_a_fighter_repeats() {
+        // Untimed matches log 0 frames left throughout, so only a repeated fighter can end a frame.
+        let log = [fighter_line(0, 0), fighter_line(0, 1), fighter_line(0, 0), fighter_line(0, 1), fighter_line(0, 0)]
+            .join("\n");
+        let (frames, errors) = frames(&log);
+        assert!(errors.is_empty());
+        let fighters: Vec<usize> = frames.iter().map(|frame| frame.fighters.len()).collect();
+        assert_eq!(fighters, vec![2, 2, 1]);
+        let indices: Vec<usize> = frames.iter().map(|frame| frame.index).collect();
+        assert_eq!(indices, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn splits_timed_frames_when_the_timer_changes() {
+        let log = [fighter_line(600, 0), fighter_line(600, 1), fighter_line(599, 1), fighter_line(599, 0)].join("\n");
+        let (frames, _) = frames(&log);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].num_frames_left, 600);
+        assert_eq!(frames[1].num_frames_left, 599);
+        assert_eq!(frames[1].fighters.len(), 2);
+    }
+
+    #[test]
+    fn puts_events_on_the_frame_they_were_logged_in() {
+        let log = [
+            event_line("match_start"),
+            fighter_line(0, 0),
+            fighter_line(0, 1),
+            event_line("frame_time"),
+            fighter_line(0, 0),
+            event_line("camera"),
+            fighter_line(0, 1),
+        ]
+        .join("\n");
+        let (frames, _) = frames(&log);
+        assert_eq!(frames.len(), 2);
+        let events: Vec<Vec<&str>> = frames
+            .iter()
+            .map(|frame| frame.events.iter().map(|event| event.event.as_str()).collect())
+            .collect();
+        assert_eq!(events, vec![vec!["match_start", "frame_time"], vec!["camera"]]);
+        assert_eq!(frames[0].timestamp_us(), Some(1));
+    }
+}
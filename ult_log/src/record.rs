@@ -0,0 +1,127 @@
+// Types for the per-frame JSON lines the plugin writes to `sd:/{replay_id}-{timestamp}.txt`.
+
+use serde::{ Deserialize, Serialize };
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+// One fighter on one frame. Written by `once_per_frame_per_fighter`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FighterFrame {
+    pub num_frames_left: u32,
+    pub fighter_id: i32,
+    // The fighter kind from `utility::get_kind`.
+    pub fighter_name: i32,
+    pub stock_count: u8,
+    pub status_kind: i32,
+    // A Hash40 of the current motion.
+    pub motion_kind: u64,
+    pub damage: f32,
+    pub shield_size: f32,
+    pub facing: f32,
+    pub pos_x: f32,
+    pub pos_y: f32,
+    pub hitstun_left: f32,
+    pub attack_connected: bool,
+    pub animation_frame_num: f32,
+    pub can_act: bool,
+    #[serde(default)]
+    pub camera_position: Vec3,
+    #[serde(default)]
+    pub camera_target_position: Vec3,
+    #[serde(default)]
+    pub camera_fov: f32,
+    pub stage_id: i32,
+}
+
+// Anything the plugin logs that isn't a fighter frame. These have an "event" key naming them, the rest of the
+// fields depend on the event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEvent {
+    pub event: String,
+    #[serde(flatten)]
+    pub fields: serde_json::Map<String, Value>,
+}
+
+// The full camera state, logged once per frame as a "camera" event. Older logs only have the camera fields on
+// each fighter record.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraBlock {
+    pub position: Vec3,
+    pub internal_position: Vec3,
+    pub target_position: Vec3,
+    pub fov: f32,
+    // The resolution the game was rendering at, which is lower handheld.
+    pub screen_width: u32,
+    pub screen_height: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogLine {
+    Fighter(Box<FighterFrame>),
+    Event(LogEvent),
+}
+
+// Every fighter's record for a single frame of the match.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Frame {
+    // Position of this frame in the log, starting from 0.
+    pub index: usize,
+    pub num_frames_left: u32,
+    pub fighters: Vec<FighterFrame>,
+    // Events logged between this frame's fighter records.
+    pub events: Vec<LogEvent>,
+}
+
+impl Frame {
+    pub fn fighter(&self, fighter_id: i32) -> Option<&FighterFrame> {
+        self.fighters.iter().find(|fighter| fighter.fighter_id == fighter_id)
+    }
+
+    // Microseconds since the Switch booted, from the frame's "frame_time" event.
+    pub fn timestamp_us(&self) -> Option<u64> {
+        let event = self.events.iter().find(|event| event.event == "frame_time")?;
+        event.fields.get("timestamp_us")?.as_u64()
+    }
+
+    pub fn camera(&self) -> Option<CameraBlock> {
+        let event = self.events.iter().find(|event| event.event == "camera")?;
+        serde_json::from_value(Value::Object(event.fields.clone())).ok()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FighterInfo {
+    pub fighter_id: i32,
+    pub fighter_name: i32,
+}
+
+// What we know about the match as a whole, from the file name and the first frame.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct MatchHeader {
+    pub replay_id: Option<String>,
+    // Milliseconds since the epoch when the log was written.
+    pub recorded_at: Option<u64>,
+    pub stage_id: Option<i32>,
+    pub fighters: Vec<FighterInfo>,
+}
+
+impl MatchHeader {
+    // Pull the replay ID and timestamp out of a `{replay_id}-{timestamp}.txt` file name.
+    pub fn from_file_name(file_name: &str) -> MatchHeader {
+        let stem = file_name.strip_suffix(".txt").unwrap_or(file_name);
+        match stem.rsplit_once('-') {
+            Some((replay_id, timestamp)) => MatchHeader {
+                replay_id: Some(replay_id.to_string()),
+                recorded_at: timestamp.parse().ok(),
+                ..MatchHeader::default()
+            },
+            None => MatchHeader::default(),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+// Replay IDs as typed into the Shared Content search, shared with the plugin like `queue`.
+
+use std::fmt;
+
+// Shared Content IDs are always 8 characters.
+pub const REPLAY_ID_LEN: usize = 8;
+
+// A replay ID that is safe to type into the software keyboard: exactly REPLAY_ID_LEN characters, each an ASCII
+// digit or uppercase letter. Only O and I are ruled out (by `normalize`); we don't otherwise try to guess which
+// letters Shared Content hands out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReplayId {
+    chars: [u8; REPLAY_ID_LEN],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReplayIdError {
+    WrongLength(usize),
+    InvalidChar(char),
+}
+
+impl fmt::Display for ReplayIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayIdError::WrongLength(len) => write!(f, "expected {} characters, got {}", REPLAY_ID_LEN, len),
+            ReplayIdError::InvalidChar(c) => write!(f, "invalid character {:?}", c),
+        }
+    }
+}
+
+impl std::error::Error for ReplayIdError {}
+
+// Clean up a character from a sheet. IDs get copied by hand, so we upper-case everything and treat O and I as the
+// 0 and 1 they almost certainly were.
+fn normalize(c: char) -> char {
+    match c.to_ascii_uppercase() {
+        'O' => '0',
+        'I' => '1',
+        c => c,
+    }
+}
+
+impl ReplayId {
+    pub fn parse(raw: &str) -> Result<ReplayId, ReplayIdError> {
+        // Allow the ID to be written with separators, e.g. "ABCD-1234" or "ABCD 1234".
+        let normalized: Vec<char> = raw
+            .trim()
+            .chars()
+            .filter(|c| *c != '-' && *c != ' ')
+            .map(normalize)
+            .collect();
+
+        if normalized.len() != REPLAY_ID_LEN {
+            return Err(ReplayIdError::WrongLength(normalized.len()));
+        }
+
+        let mut chars = [0u8; REPLAY_ID_LEN];
+        for (i, c) in normalized.iter().enumerate() {
+            if !c.is_ascii_digit() && !c.is_ascii_uppercase() {
+                return Err(ReplayIdError::InvalidChar(*c));
+            }
+            chars[i] = *c as u8;
+        }
+        Ok(ReplayId { chars })
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only ASCII makes it through parse.
+        std::str::from_utf8(&self.chars).unwrap()
+    }
+
+    // The null-terminated UTF-16 the software keyboard expects.
+    pub fn to_utf16(&self) -> [u16; REPLAY_ID_LEN + 1] {
+        let mut utf16 = [0u16; REPLAY_ID_LEN + 1];
+        for (i, c) in self.chars.iter().enumerate() {
+            utf16[i] = *c as u16;
+        }
+        utf16
+    }
+}
+
+impl fmt::Display for ReplayId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ids_with_separators() {
+        assert_eq!(ReplayId::parse("ABCD1234").unwrap().as_str(), "ABCD1234");
+        assert_eq!(ReplayId::parse("ABCD-1234").unwrap().as_str(), "ABCD1234");
+        assert_eq!(ReplayId::parse(" ABCD 1234\n").unwrap().as_str(), "ABCD1234");
+    }
+
+    #[test]
+    fn normalizes_hand_copied_ids() {
+        assert_eq!(ReplayId::parse("abcd1234").unwrap().as_str(), "ABCD1234");
+        assert_eq!(ReplayId::parse("OIoi5678").unwrap().as_str(), "01015678");
+    }
+
+    #[test]
+    fn rejects_the_wrong_length() {
+        assert_eq!(ReplayId::parse("ABCD123"), Err(ReplayIdError::WrongLength(7)));
+        assert_eq!(ReplayId::parse("ABCD-12345"), Err(ReplayIdError::WrongLength(9)));
+        assert_eq!(ReplayId::parse(""), Err(ReplayIdError::WrongLength(0)));
+    }
+
+    #[test]
+    fn rejects_invalid_characters() {
+        assert_eq!(ReplayId::parse("ABCD_123"), Err(ReplayIdError::InvalidChar('_')));
+        assert_eq!(ReplayId::parse("ABCD123\u{c9}"), Err(ReplayIdError::InvalidChar('\u{c9}')));
+    }
+
+    #[test]
+    fn converts_to_null_terminated_utf16() {
+        let utf16 = ReplayId::parse("ABCD1234").unwrap().to_utf16();
+        assert_eq!(utf16.len(), REPLAY_ID_LEN + 1);
+        assert_eq!(String::from_utf16(&utf16[..REPLAY_ID_LEN]).unwrap(), "ABCD1234");
+        assert_eq!(utf16[REPLAY_ID_LEN], 0);
+    }
+}
@@ -0,0 +1,263 @@
+// Import match logs into a local SQLite database so they can be queried together.
+//
+// Each match is keyed by its replay ID (see `match_key`). Importing the same recording again is a no-op, and a
+// newer recording of the same replay replaces the old one, so a whole folder of logs can be re-imported as new
+// ones come in.
+
+use rusqlite::{ params, Connection, OptionalExtension };
+use std::path::Path;
+
+use crate::reader::Match;
+use crate::replay_id::ReplayId;
+
+pub use rusqlite::Error as SqliteError;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS matches (
+    replay_id TEXT PRIMARY KEY,
+    recorded_at INTEGER,
+    stage_id INTEGER,
+    duration_frames INTEGER NOT NULL,
+    malformed_lines INTEGER NOT NULL,
+    truncated INTEGER NOT NULL,
+    source_path TEXT
+);
+CREATE TABLE IF NOT EXISTS fighters (
+    replay_id TEXT NOT NULL REFERENCES matches(replay_id) ON DELETE CASCADE,
+    fighter_id INTEGER NOT NULL,
+    fighter_name INTEGER NOT NULL,
+    PRIMARY KEY (replay_id, fighter_id)
+);
+CREATE TABLE IF NOT EXISTS frames (
+    replay_id TEXT NOT NULL REFERENCES matches(replay_id) ON DELETE CASCADE,
+    frame INTEGER NOT NULL,
+    fighter_id INTEGER NOT NULL,
+    num_frames_left INTEGER NOT NULL,
+    fighter_name INTEGER NOT NULL,
+    stock_count INTEGER NOT NULL,
+    status_kind INTEGER NOT NULL,
+    motion_kind INTEGER NOT NULL,
+    damage REAL NOT NULL,
+    shield_size REAL NOT NULL,
+    facing REAL NOT NULL,
+    pos_x REAL NOT NULL,
+    pos_y REAL NOT NULL,
+    hitstun_left REAL NOT NULL,
+    attack_connected INTEGER NOT NULL,
+    animation_frame_num REAL NOT NULL,
+    can_act INTEGER NOT NULL,
+    camera_position_x REAL NOT NULL,
+    camera_position_y REAL NOT NULL,
+    camera_position_z REAL NOT NULL,
+    camera_target_position_x REAL NOT NULL,
+    camera_target_position_y REAL NOT NULL,
+    camera_target_position_z REAL NOT NULL,
+    camera_fov REAL NOT NULL,
+    stage_id INTEGER NOT NULL,
+    PRIMARY KEY (replay_id, frame, fighter_id)
+);
+CREATE TABLE IF NOT EXISTS events (
+    replay_id TEXT NOT NULL REFERENCES matches(replay_id) ON DELETE CASCADE,
+    frame INTEGER NOT NULL,
+    event TEXT NOT NULL,
+    fields TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS events_by_replay ON events (replay_id, frame);
+";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportResult {
+    Imported,
+    // A newer recording of a replay we already had.
+    Replaced,
+    // We already have this recording (or a newer one).
+    Skipped,
+}
+
+pub fn open<P: AsRef<Path>>(path: P) -> Result<Connection, SqliteError> {
+    let connection = Connection::open(path)?;
+    connection.execute_batch("PRAGMA foreign_keys = ON;")?;
+    connection.execute_batch(SCHEMA)?;
+    Ok(connection)
+}
+
+// What to key a match by: its replay ID, if the file name starts with one. Matches the plugin didn't queue are
+// named after their fighters instead, so those are keyed by the whole file name, timestamp included, to keep
+// different matches between the same fighters apart.
+pub fn match_key(log: &Match, source_path: &str) -> String {
+    if let Some(replay_id) = log.header.replay_id.as_deref().and_then(|raw_id| ReplayId::parse(raw_id).ok()) {
+        return replay_id.to_string();
+    }
+    Path::new(source_path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(source_path)
+        .to_string()
+}
+
+// Import one match under `replay_id`, normally from `match_key`.
+pub fn import_match(
+    connection: &mut Connection,
+    log: &Match,
+    replay_id: &str,
+    source_path: Option<&str>,
+    force: bool
+) -> Result<ImportResult, SqliteError> {
+    let existing: Option<Option<i64>> = connection
+        .query_row("SELECT recorded_at FROM matches WHERE replay_id = ?1", params![replay_id], |row| row.get(0))
+        .optional()?;
+    let recorded_at = log.header.recorded_at.map(|recorded_at| recorded_at as i64);
+
+    let result = match existing {
+        None => ImportResult::Imported,
+        Some(existing_recorded_at) => {
+            let is_newer = match (recorded_at, existing_recorded_at) {
+                (Some(new), Some(old)) => new > old,
+                _ => false,
+            };
+            if !force && !is_newer {
+                return Ok(ImportResult::Skipped);
+            }
+            ImportResult::Replaced
+        }
+    };
+
+    let transaction = connection.transaction()?;
+    transaction.execute("DELETE FROM matches WHERE replay_id = ?1", params![replay_id])?;
+    transaction.execute(
+        "INSERT INTO matches (replay_id, recorded_at, stage_id, duration_frames, malformed_lines, truncated, source_path)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            replay_id,
+            recorded_at,
+            log.header.stage_id,
+            log.frames.len() as i64,
+            log.malformed_lines() as i64,
+            log.is_truncated(),
+            source_path
+        ]
+    )?;
+
+    {
+        let mut insert_fighter = transaction.prepare(
+            "INSERT INTO fighters (replay_id, fighter_id, fighter_name) VALUES (?1, ?2, ?3)"
+        )?;
+        for fighter in &log.header.fighters {
+            insert_fighter.execute(params![replay_id, fighter.fighter_id, fighter.fighter_name])?;
+        }
+
+        let mut insert_frame = transaction.prepare(
+            "INSERT OR REPLACE INTO frames VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22,
+                ?23, ?24, ?25
+            )"
+        )?;
+        let mut insert_event = transaction.prepare(
+            "INSERT INTO events (replay_id, frame, event, fields) VALUES (?1, ?2, ?3, ?4)"
+        )?;
+        for frame in &log.frames {
+            for f in &frame.fighters {
+                insert_frame.execute(params![
+                    replay_id,
+                    frame.index as i64,
+                    f.fighter_id,
+                    f.num_frames_left,
+                    f.fighter_name,
+                    f.stock_count,
+                    f.status_kind,
+                    // SQLite integers are signed, so the Hash40 is stored as its bit pattern.
+                    f.motion_kind as i64,
+                    f.damage,
+                    f.shield_size,
+                    f.facing,
+                    f.pos_x,
+                    f.pos_y,
+                    f.hitstun_left,
+                    f.attack_connected,
+                    f.animation_frame_num,
+                    f.can_act,
+                    f.camera_position.x,
+                    f.camera_position.y,
+                    f.camera_position.z,
+                    f.camera_target_position.x,
+                    f.camera_target_position.y,
+                    f.camera_target_position.z,
+                    f.camera_fov,
+                    f.stage_id
+                ])?;
+            }
+            for event in &frame.events {
+                let fields = serde_json::Value::Object(event.fields.clone()).to_string();
+                insert_event.execute(params![replay_id, frame.index as i64, event.event, fields])?;
+            }
+        }
+    }
+
+    transaction.commit()?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::{ FighterFrame, Frame, MatchHeader };
+
+    fn log(file_name: &str, frames: usize) -> Match {
+        let fighter: FighterFrame = serde_json::from_str(
+            r#"{"num_frames_left":0,"fighter_id":0,"fighter_name":1,"stock_count":3,"status_kind":0,
+                "motion_kind":0,"damage":0.0,"shield_size":50.0,"facing":1.0,"pos_x":0.0,"pos_y":0.0,
+                "hitstun_left":0.0,"attack_connected":false,"animation_frame_num":0.0,"can_act":true,"stage_id":0}"#,
+        )
+        .unwrap();
+        Match {
+            header: MatchHeader::from_file_name(file_name),
+            frames: (0..frames)
+                .map(|index| Frame { index, fighters: vec![fighter.clone()], ..Frame::default() })
+                .collect(),
+            errors: vec![],
+        }
+    }
+
+    fn import(connection: &mut Connection, file_name: &str, frames: usize, force: bool) -> ImportResult {
+        let log = log(file_name, frames);
+        let key = match_key(&log, file_name);
+        import_match(connection, &log, &key, Some(file_name), force).unwrap()
+    }
+
+    fn duration(connection: &Connection, replay_id: &str) -> i64 {
+        connection
+            .query_row("SELECT duration_frames FROM matches WHERE replay_id = ?1", params![replay_id], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn keys_by_replay_id_or_file_stem() {
+        assert_eq!(match_key(&log("abcd-1234-1700.txt", 1), "logs/abcd-1234-1700.txt"), "ABCD1234");
+        assert_eq!(match_key(&log("fight-3-vs-4-1700.txt", 1), "logs/fight-3-vs-4-1700.txt"), "fight-3-vs-4-1700");
+        assert_eq!(match_key(&log("vt.txt", 1), "vt.txt"), "vt");
+    }
+
+    #[test]
+    fn dedups_and_replaces_with_newer_recordings() {
+        let mut connection = open(":memory:").unwrap();
+        assert_eq!(import(&mut connection, "ABCD1234-1700.txt", 200, false), ImportResult::Imported);
+        assert_eq!(import(&mut connection, "ABCD1234-1700.txt", 200, false), ImportResult::Skipped);
+        assert_eq!(import(&mut connection, "ABCD1234-1600.txt", 25, false), ImportResult::Skipped);
+        assert_eq!(duration(&connection, "ABCD1234"), 200);
+
+        assert_eq!(import(&mut connection, "ABCD1234-1800.txt", 150, false), ImportResult::Replaced);
+        assert_eq!(duration(&connection, "ABCD1234"), 150);
+
+        assert_eq!(import(&mut connection, "ABCD1234-1600.txt", 25, true), ImportResult::Replaced);
+        assert_eq!(duration(&connection, "ABCD1234"), 25);
+    }
+
+    #[test]
+    fn unqueued_matches_between_the_same_fighters_are_kept_apart() {
+        let mut connection = open(":memory:").unwrap();
+        assert_eq!(import(&mut connection, "fight-3-vs-4-1700.txt", 200, false), ImportResult::Imported);
+        assert_eq!(import(&mut connection, "fight-3-vs-4-1800.txt", 25, false), ImportResult::Imported);
+        assert_eq!(duration(&connection, "fight-3-vs-4-1700"), 200);
+        assert_eq!(duration(&connection, "fight-3-vs-4-1800"), 25);
+    }
+}
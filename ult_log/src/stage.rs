@@ -0,0 +1,295 @@
+// Stage geometry, for telling where a fighter is relative to the stage from their raw `pos_x`/`pos_y`.
+//
+// The embedded stages are measured in game units and are close enough to classify positions, not exact. Other
+// stages can be added, or these replaced, from a JSON file with a list of `StageGeometry`:
+//   [{"stage_id": 2, "name": "Final Destination", "left_ledge": {"x": -85.7, "y": 0.0}, ...}]
+
+use serde::{ Deserialize, Serialize };
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::reader::Match;
+
+// How far above a platform a fighter can be and still be standing on it, for float noise.
+const STANDING_EPSILON: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+// A pass-through platform, as the height of its top and its left and right ends.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Platform {
+    pub left: f32,
+    pub right: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BlastZones {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageGeometry {
+    pub stage_id: i32,
+    pub name: String,
+    // The corners of the main stage that can be grabbed.
+    pub left_ledge: Point,
+    pub right_ledge: Point,
+    #[serde(default)]
+    pub platforms: Vec<Platform>,
+    pub blast_zones: BlastZones,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Region {
+    // Over the main stage, on the ground or in the air.
+    Onstage,
+    // Standing on one of the stage's platforms, by index.
+    OnPlatform(usize),
+    // Out past a ledge, or below the stage.
+    Offstage,
+}
+
+impl StageGeometry {
+    fn center_x(&self) -> f32 {
+        (self.left_ledge.x + self.right_ledge.x) / 2.0
+    }
+
+    fn floor_y(&self) -> f32 {
+        self.left_ledge.y.min(self.right_ledge.y)
+    }
+
+    // Scales a position so the ledges are at x = -1 and 1, the stage floor is at y = 0 and the top blast zone
+    // is at y = 1. Makes positions comparable across stages.
+    pub fn normalize(&self, x: f32, y: f32) -> Point {
+        let half_width = (self.right_ledge.x - self.left_ledge.x) / 2.0;
+        Point {
+            x: (x - self.center_x()) / half_width,
+            y: (y - self.floor_y()) / (self.blast_zones.top - self.floor_y()),
+        }
+    }
+
+    // Distance to whichever ledge is closer.
+    pub fn distance_to_ledge(&self, x: f32, y: f32) -> f32 {
+        let distance = |ledge: &Point| ((x - ledge.x).powi(2) + (y - ledge.y).powi(2)).sqrt();
+        distance(&self.left_ledge).min(distance(&self.right_ledge))
+    }
+
+    // Distance to whichever blast zone is closer. Negative once past one.
+    pub fn distance_to_blast_zone(&self, x: f32, y: f32) -> f32 {
+        let zones = &self.blast_zones;
+        (x - zones.left).min(zones.right - x).min(zones.top - y).min(y - zones.bottom)
+    }
+
+    pub fn classify(&self, x: f32, y: f32) -> Region {
+        let ledge_y = if x < self.center_x() { self.left_ledge.y } else { self.right_ledge.y };
+        if x < self.left_ledge.x || x > self.right_ledge.x || y < ledge_y - STANDING_EPSILON {
+            return Region::Offstage;
+        }
+        let platform = self.platforms.iter().position(|platform| {
+            x >= platform.left && x <= platform.right && (y - platform.y).abs() <= STANDING_EPSILON
+        });
+        match platform {
+            Some(index) => Region::OnPlatform(index),
+            None => Region::Onstage,
+        }
+    }
+}
+
+fn point(x: f32, y: f32) -> Point {
+    Point { x, y }
+}
+
+fn platform(left: f32, right: f32, y: f32) -> Platform {
+    Platform { left, right, y }
+}
+
+pub fn builtin_stages() -> Vec<StageGeometry> {
+    vec![
+        StageGeometry {
+            stage_id: 0x00,
+            name: "Battlefield".to_string(),
+            left_ledge: point(-71.3, 0.0),
+            right_ledge: point(71.3, 0.0),
+            platforms: vec![platform(-57.6, -20.0, 27.2), platform(20.0, 57.6, 27.2), platform(-18.8, 18.8, 54.4)],
+            blast_zones: BlastZones { left: -224.0, right: 224.0, top: 200.0, bottom: -108.8 },
+        },
+        StageGeometry {
+            stage_id: 0x02,
+            name: "Final Destination".to_string(),
+            left_ledge: point(-85.7, 0.0),
+            right_ledge: point(85.7, 0.0),
+            platforms: vec![],
+            blast_zones: BlastZones { left: -235.5, right: 235.5, top: 180.0, bottom: -140.0 },
+        },
+        StageGeometry {
+            stage_id: 0x12,
+            name: "Yoshi's Story".to_string(),
+            left_ledge: point(-56.0, 0.0),
+            right_ledge: point(56.0, 0.0),
+            platforms: vec![platform(-59.5, -28.0, 23.5), platform(28.0, 59.5, 23.5), platform(-15.8, 15.8, 42.0)],
+            blast_zones: BlastZones { left: -175.0, right: 175.0, top: 168.0, bottom: -91.0 },
+        },
+        // The main stage tilts, so the ledges sit a little below the floor.
+        StageGeometry {
+            stage_id: 0x26,
+            name: "Lylat Cruise".to_string(),
+            left_ledge: point(-67.5, -6.5),
+            right_ledge: point(67.5, -6.5),
+            platforms: vec![platform(-55.0, -26.0, 23.0), platform(26.0, 55.0, 23.0), platform(-17.0, 17.0, 44.0)],
+            blast_zones: BlastZones { left: -210.0, right: 210.0, top: 190.0, bottom: -125.0 },
+        },
+        StageGeometry {
+            stage_id: 0x27,
+            name: "Pokemon Stadium 2".to_string(),
+            left_ledge: point(-76.0, 0.0),
+            right_ledge: point(76.0, 0.0),
+            platforms: vec![platform(-54.0, -26.0, 25.0), platform(26.0, 54.0, 25.0)],
+            blast_zones: BlastZones { left: -230.0, right: 230.0, top: 180.0, bottom: -110.0 },
+        },
+        // The platform moves back and forth, so standing on it is classified as onstage.
+        StageGeometry {
+            stage_id: 0x2b,
+            name: "Smashville".to_string(),
+            left_ledge: point(-70.0, 0.0),
+            right_ledge: point(70.0, 0.0),
+            platforms: vec![],
+            blast_zones: BlastZones { left: -225.0, right: 225.0, top: 200.0, bottom: -110.0 },
+        },
+        StageGeometry {
+            stage_id: 0x4e,
+            name: "Kalos Pokemon League".to_string(),
+            left_ledge: point(-78.5, 0.0),
+            right_ledge: point(78.5, 0.0),
+            platforms: vec![platform(-60.0, -27.0, 28.0), platform(27.0, 60.0, 28.0), platform(-19.0, 19.0, 55.0)],
+            blast_zones: BlastZones { left: -240.0, right: 240.0, top: 210.0, bottom: -110.0 },
+        },
+        // Like Smashville, the platforms move, so they're left out.
+        StageGeometry {
+            stage_id: 0x54,
+            name: "Town and City".to_string(),
+            left_ledge: point(-73.5, 0.0),
+            right_ledge: point(73.5, 0.0),
+            platforms: vec![],
+            blast_zones: BlastZones { left: -220.0, right: 220.0, top: 200.0, bottom: -110.0 },
+        },
+        StageGeometry {
+            stage_id: 0x13c,
+            name: "Hollow Bastion".to_string(),
+            left_ledge: point(-67.0, 0.0),
+            right_ledge: point(67.0, 0.0),
+            platforms: vec![],
+            blast_zones: BlastZones { left: -220.0, right: 220.0, top: 200.0, bottom: -110.0 },
+        },
+        StageGeometry {
+            stage_id: 0x13d,
+            name: "Small Battlefield".to_string(),
+            left_ledge: point(-71.3, 0.0),
+            right_ledge: point(71.3, 0.0),
+            platforms: vec![platform(-55.0, -20.0, 30.0), platform(20.0, 55.0, 30.0)],
+            blast_zones: BlastZones { left: -224.0, right: 224.0, top: 200.0, bottom: -108.8 },
+        },
+    ]
+}
+
+#[derive(Debug)]
+pub enum StagesError {
+    Io(io::Error),
+    Malformed(String),
+}
+
+impl fmt::Display for StagesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StagesError::Io(e) => write!(f, "couldn't read stages: {}", e),
+            StagesError::Malformed(message) => write!(f, "malformed stages: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for StagesError {}
+
+impl From<io::Error> for StagesError {
+    fn from(e: io::Error) -> Self {
+        StagesError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StageTable {
+    stages: HashMap<i32, StageGeometry>,
+}
+
+impl StageTable {
+    pub fn embedded() -> StageTable {
+        StageTable { stages: builtin_stages().into_iter().map(|stage| (stage.stage_id, stage)).collect() }
+    }
+
+    // Adds the stages from a JSON file, replacing any embedded stage with the same ID.
+    pub fn extend_from_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), StagesError> {
+        let stages: Vec<StageGeometry> =
+            serde_json::from_str(&fs::read_to_string(path)?).map_err(|e| StagesError::Malformed(e.to_string()))?;
+        self.stages.extend(stages.into_iter().map(|stage| (stage.stage_id, stage)));
+        Ok(())
+    }
+
+    pub fn get(&self, stage_id: i32) -> Option<&StageGeometry> {
+        self.stages.get(&stage_id)
+    }
+}
+
+// Where one fighter is on one frame, relative to the stage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StagePosition {
+    pub frame: usize,
+    pub fighter_id: i32,
+    pub region: Region,
+    pub normalized: Point,
+    pub distance_to_ledge: f32,
+    pub distance_to_blast_zone: f32,
+}
+
+// Every fighter's position on every frame. Empty if we don't have geometry for the match's stage.
+pub fn stage_positions(log: &Match, stages: &StageTable) -> Vec<StagePosition> {
+    let stage = match log.header.stage_id.and_then(|stage_id| stages.get(stage_id)) {
+        Some(stage) => stage,
+        None => return vec![],
+    };
+    log.frames
+        .iter()
+        .flat_map(|frame| {
+            frame.fighters.iter().map(move |fighter| StagePosition {
+                frame: frame.index,
+                fighter_id: fighter.fighter_id,
+                region: stage.classify(fighter.pos_x, fighter.pos_y),
+                normalized: stage.normalize(fighter.pos_x, fighter.pos_y),
+                distance_to_ledge: stage.distance_to_ledge(fighter.pos_x, fighter.pos_y),
+                distance_to_blast_zone: stage.distance_to_blast_zone(fighter.pos_x, fighter.pos_y),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::names;
+
+    #[test]
+    fn builtin_stage_ids_match_names() {
+        for stage in builtin_stages() {
+            assert_eq!(names::stage_name(stage.stage_id), Some(stage.name.as_str()), "stage {:#x}", stage.stage_id);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+// The standard competitive stats for a match, worked out from its conversions.
+
+use serde::{ Deserialize, Serialize };
+use std::collections::HashMap;
+
+use crate::conversions::{ self, Conversion, ConversionOptions };
+use crate::name_tables::NameTables;
+use crate::reader::Match;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PunishStarter {
+    pub motion_kind: u64,
+    pub motion: Option<String>,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub fighter_id: i32,
+    pub fighter_name: i32,
+    pub fighter: Option<String>,
+    // Conversions this player started, and how many of those were from neutral rather than counter-hits.
+    pub openings: usize,
+    pub neutral_wins: usize,
+    pub counter_hits: usize,
+    pub kills: usize,
+    pub total_damage: f32,
+    // None when there's nothing to divide by.
+    pub openings_per_kill: Option<f32>,
+    pub damage_per_opening: Option<f32>,
+    pub average_kill_percent: Option<f32>,
+    // The moves this player opened conversions with, most used first.
+    pub punish_starters: Vec<PunishStarter>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsReport {
+    pub replay_id: Option<String>,
+    pub stage_id: Option<i32>,
+    pub players: Vec<PlayerStats>,
+    pub conversions: Vec<Conversion>,
+}
+
+fn ratio(numerator: f32, denominator: usize) -> Option<f32> {
+    if denominator == 0 {
+        None
+    } else {
+        Some(numerator / denominator as f32)
+    }
+}
+
+fn player_stats(fighter_id: i32, fighter_name: i32, conversions: &[Conversion], names: &NameTables) -> PlayerStats {
+    let started: Vec<&Conversion> = conversions.iter().filter(|conversion| conversion.attacker_id == fighter_id).collect();
+    let kills: Vec<&Conversion> = started.iter().copied().filter(|conversion| conversion.killed).collect();
+    let neutral_wins = started.iter().filter(|conversion| conversion.neutral_win).count();
+    let total_damage: f32 = started.iter().map(|conversion| conversion.damage()).sum();
+    let kill_percent: f32 = kills.iter().map(|conversion| conversion.end_percent).sum();
+
+    let mut starter_counts: HashMap<u64, usize> = HashMap::new();
+    for conversion in &started {
+        *starter_counts.entry(conversion.opening_motion_kind()).or_insert(0) += 1;
+    }
+    let mut punish_starters: Vec<PunishStarter> = starter_counts
+        .into_iter()
+        .map(|(motion_kind, count)| PunishStarter {
+            motion_kind,
+            motion: names.motion(motion_kind).map(str::to_string),
+            count,
+        })
+        .collect();
+    punish_starters.sort_by(|a, b| b.count.cmp(&a.count).then(a.motion_kind.cmp(&b.motion_kind)));
+
+    PlayerStats {
+        fighter_id,
+        fighter_name,
+        fighter: names.fighter(fighter_name).map(str::to_string),
+        openings: started.len(),
+        neutral_wins,
+        counter_hits: started.len() - neutral_wins,
+        kills: kills.len(),
+        total_damage,
+        openings_per_kill: ratio(started.len() as f32, kills.len()),
+        damage_per_opening: ratio(total_damage, started.len()),
+        average_kill_percent: ratio(kill_percent, kills.len()),
+        punish_starters,
+    }
+}
+
+impl StatsReport {
+    pub fn from_match(log: &Match, names: &NameTables) -> StatsReport {
+        let conversions = conversions::find_conversions(log, &ConversionOptions::default());
+        let players = log
+            .header
+            .fighters
+            .iter()
+            .map(|info| player_stats(info.fighter_id, info.fighter_name, &conversions, names))
+            .collect();
+
+        StatsReport {
+            replay_id: log.header.replay_id.clone(),
+            stage_id: log.header.stage_id,
+            players,
+            conversions,
+        }
+    }
+}
@@ -0,0 +1,66 @@
+// A one-glance summary of a recorded match.
+
+use serde::{ Deserialize, Serialize };
+
+use crate::name_tables::NameTables;
+use crate::reader::Match;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FighterSummary {
+    pub fighter_id: i32,
+    pub fighter_name: i32,
+    // The fighter's name, if we have one for `fighter_name`.
+    pub fighter: Option<String>,
+    pub starting_stocks: u8,
+    pub final_stocks: u8,
+    pub stocks_lost: u8,
+    pub final_damage: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchSummary {
+    pub replay_id: Option<String>,
+    pub stage_id: Option<i32>,
+    pub stage: Option<String>,
+    pub duration_frames: usize,
+    pub fighters: Vec<FighterSummary>,
+    pub malformed_lines: usize,
+    pub truncated: bool,
+}
+
+impl MatchSummary {
+    pub fn from_match(log: &Match, names: &NameTables) -> MatchSummary {
+        let fighters = log
+            .header
+            .fighters
+            .iter()
+            .map(|info| {
+                let mut records = log.frames.iter().filter_map(|frame| frame.fighter(info.fighter_id));
+                let first = records.next();
+                let last = records.next_back().or(first);
+
+                let starting_stocks = first.map(|fighter| fighter.stock_count).unwrap_or(0);
+                let final_stocks = last.map(|fighter| fighter.stock_count).unwrap_or(0);
+                FighterSummary {
+                    fighter_id: info.fighter_id,
+                    fighter_name: info.fighter_name,
+                    fighter: names.fighter(info.fighter_name).map(str::to_string),
+                    starting_stocks,
+                    final_stocks,
+                    stocks_lost: starting_stocks.saturating_sub(final_stocks),
+                    final_damage: last.map(|fighter| fighter.damage).unwrap_or(0.0),
+                }
+            })
+            .collect();
+
+        MatchSummary {
+            replay_id: log.header.replay_id.clone(),
+            stage_id: log.header.stage_id,
+            stage: log.header.stage_id.and_then(|stage_id| names.stage(stage_id)).map(str::to_string),
+            duration_frames: log.frames.len(),
+            fighters,
+            malformed_lines: log.malformed_lines(),
+            truncated: log.is_truncated(),
+        }
+    }
+}
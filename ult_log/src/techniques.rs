@@ -0,0 +1,240 @@
+// Labels technical actions (techs, ledge options, shield drops, ...) by matching patterns against each fighter's
+// sequence of statuses.
+//
+// A fighter's frames are collapsed into runs of the same `status_kind`, and a pattern is a list of steps that
+// have to match consecutive runs. Steps refer to statuses by name (see `names::STATUS_NAMES`). Every status the
+// built-in patterns use is in the embedded tables; rules using any other status need a names file. Patterns can
+// also be loaded from JSON:
+//   [{"label": "ledge_getup", "steps": [{"statuses": ["CLIFF_WAIT"]}, {"statuses": ["CLIFF_CLIMB"]}]}]
+
+use serde::{ Deserialize, Serialize };
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::name_tables::NameTables;
+use crate::reader::Match;
+
+// How soon after a tech a hit has to land to count as a tech chase.
+pub const TECH_CHASE_FRAMES: usize = 30;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    // Any of these statuses matches the step.
+    pub statuses: Vec<String>,
+    // How long the status has to last, in frames.
+    #[serde(default)]
+    pub min_frames: usize,
+    #[serde(default)]
+    pub max_frames: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pattern {
+    pub label: String,
+    pub steps: Vec<Step>,
+}
+
+// A matched action, from the first frame of its first step to the last frame of its last.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Action {
+    pub fighter_id: i32,
+    pub label: String,
+    pub start_frame: usize,
+    pub end_frame: usize,
+}
+
+#[derive(Debug)]
+pub enum RulesError {
+    Io(io::Error),
+    Malformed(String),
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesError::Io(e) => write!(f, "couldn't read rules: {}", e),
+            RulesError::Malformed(message) => write!(f, "malformed rules: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+impl From<io::Error> for RulesError {
+    fn from(e: io::Error) -> Self {
+        RulesError::Io(e)
+    }
+}
+
+fn step(statuses: &[&str]) -> Step {
+    Step { statuses: statuses.iter().map(|status| status.to_string()).collect(), min_frames: 0, max_frames: None }
+}
+
+fn step_within(statuses: &[&str], max_frames: usize) -> Step {
+    Step { max_frames: Some(max_frames), ..step(statuses) }
+}
+
+fn pattern(label: &str, steps: Vec<Step>) -> Pattern {
+    Pattern { label: label.to_string(), steps }
+}
+
+const LEDGE: &[&str] = &["CLIFF_CATCH", "CLIFF_WAIT"];
+const KNOCKBACK: &[&str] = &["DAMAGE_AIR", "DAMAGE_FLY", "DAMAGE_FLY_ROLL", "DAMAGE_FLY_METEOR"];
+
+pub fn builtin_patterns() -> Vec<Pattern> {
+    vec![
+        // Techs and missed techs
+        pattern("tech_in_place", vec![step(&["PASSIVE"])]),
+        pattern("tech_roll", vec![step(&["PASSIVE_FB"])]),
+        pattern("wall_tech", vec![step(&["PASSIVE_WALL", "PASSIVE_WALL_JUMP"])]),
+        pattern("ceiling_tech", vec![step(&["PASSIVE_CEIL"])]),
+        pattern("missed_tech", vec![step(&["DOWN"]), step(&["DOWN_WAIT", "DOWN_WAIT_CONTINUE"])]),
+        // Ledge options
+        pattern("ledge_getup", vec![step(LEDGE), step(&["CLIFF_CLIMB"])]),
+        pattern("ledge_attack", vec![step(LEDGE), step(&["CLIFF_ATTACK"])]),
+        pattern("ledge_roll", vec![step(LEDGE), step(&["CLIFF_ESCAPE"])]),
+        pattern("ledge_jump", vec![step(LEDGE), step(&["CLIFF_JUMP1"]), step(&["CLIFF_JUMP2"])]),
+        pattern("ledge_drop", vec![step(&["CLIFF_WAIT"]), step(&["FALL", "FALL_AERIAL"])]),
+        // Shield
+        pattern("shield_drop", vec![step(&["GUARD_ON", "GUARD"]), step(&["PASS"])]),
+        // Releasing shield just before the hit, which the game treats as a parry
+        pattern("perfect_shield", vec![step_within(&["GUARD_OFF"], 5), step(&["GUARD_DAMAGE"])]),
+        // Airdodging into the ground out of a jump
+        pattern(
+            "wavedash",
+            vec![
+                step(&["JUMP_SQUAT"]),
+                step_within(&["ESCAPE_AIR", "ESCAPE_AIR_SLIDE"], 10),
+                step(&["LANDING", "LANDING_LIGHT", "LANDING_FALL_SPECIAL"]),
+            ],
+        ),
+        // Hits with enough knockback to launch, where the defender gets to DI
+        pattern("di_hit", vec![step(KNOCKBACK)]),
+    ]
+}
+
+pub fn parse_rules(json: &str) -> Result<Vec<Pattern>, RulesError> {
+    serde_json::from_str(json).map_err(|e| RulesError::Malformed(e.to_string()))
+}
+
+pub fn read_rules<P: AsRef<Path>>(path: P) -> Result<Vec<Pattern>, RulesError> {
+    parse_rules(&fs::read_to_string(path)?)
+}
+
+// A stretch of frames one fighter spent in the same status.
+#[derive(Debug, Clone, PartialEq)]
+struct Run {
+    status: Option<String>,
+    start_frame: usize,
+    end_frame: usize,
+}
+
+impl Run {
+    fn frames(&self) -> usize {
+        self.end_frame - self.start_frame + 1
+    }
+}
+
+fn status_runs(log: &Match, fighter_id: i32, names: &NameTables) -> Vec<Run> {
+    let mut runs: Vec<Run> = Vec::new();
+    let mut last_status_kind = None;
+    for frame in &log.frames {
+        let fighter = match frame.fighter(fighter_id) {
+            Some(fighter) => fighter,
+            None => continue,
+        };
+        match runs.last_mut() {
+            Some(run) if last_status_kind == Some(fighter.status_kind) => run.end_frame = frame.index,
+            _ => runs.push(Run {
+                status: names.status(fighter.fighter_name, fighter.status_kind).map(str::to_string),
+                start_frame: frame.index,
+                end_frame: frame.index,
+            }),
+        }
+        last_status_kind = Some(fighter.status_kind);
+    }
+    runs
+}
+
+fn step_matches(step: &Step, run: &Run) -> bool {
+    let status_matches = match &run.status {
+        Some(status) => step.statuses.iter().any(|step_status| step_status == status),
+        None => false,
+    };
+    status_matches && run.frames() >= step.min_frames && step.max_frames.map(|max| run.frames() <= max).unwrap_or(true)
+}
+
+// Techs that were followed up on by a hit soon after.
+fn tech_chases(log: &Match, actions: &[Action]) -> Vec<Action> {
+    actions
+        .iter()
+        .filter(|action| action.label.starts_with("tech_") || action.label == "missed_tech")
+        .filter_map(|tech| {
+            let mut damage = log.frames.get(tech.end_frame)?.fighter(tech.fighter_id)?.damage;
+            for frame in log.frames.iter().skip(tech.end_frame + 1).take(TECH_CHASE_FRAMES) {
+                let fighter = frame.fighter(tech.fighter_id)?;
+                if fighter.damage > damage {
+                    return Some(Action {
+                        fighter_id: tech.fighter_id,
+                        label: "tech_chase".to_string(),
+                        start_frame: tech.start_frame,
+                        end_frame: frame.index,
+                    });
+                }
+                damage = fighter.damage;
+            }
+            None
+        })
+        .collect()
+}
+
+// Every match of every pattern, for every fighter, in order of when they started.
+pub fn find_actions(log: &Match, patterns: &[Pattern], names: &NameTables) -> Vec<Action> {
+    let mut actions = Vec::new();
+    for info in &log.header.fighters {
+        let runs = status_runs(log, info.fighter_id, names);
+        for pattern in patterns.iter().filter(|pattern| !pattern.steps.is_empty()) {
+            for start in 0..runs.len() {
+                let candidate = match runs.get(start..start + pattern.steps.len()) {
+                    Some(candidate) => candidate,
+                    None => break,
+                };
+                if pattern.steps.iter().zip(candidate).all(|(step, run)| step_matches(step, run)) {
+                    actions.push(Action {
+                        fighter_id: info.fighter_id,
+                        label: pattern.label.clone(),
+                        start_frame: candidate[0].start_frame,
+                        end_frame: candidate[candidate.len() - 1].end_frame,
+                    });
+                }
+            }
+        }
+    }
+
+    let chases = tech_chases(log, &actions);
+    actions.extend(chases);
+    actions.sort_by_key(|action| (action.start_frame, action.fighter_id));
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::names;
+
+    #[test]
+    fn builtin_statuses_have_names() {
+        for pattern in builtin_patterns() {
+            for status in pattern.steps.iter().flat_map(|step| &step.statuses) {
+                assert!(
+                    names::STATUS_NAMES.iter().any(|(_, name)| name == status),
+                    "{} uses unknown status {}",
+                    pattern.label,
+                    status
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,189 @@
+// Parser for the MBST label traces written by the plugin's `trace` module (`sd:/batch-{timestamp}-trace.txt`).
+//
+// Each line is a JSON object like:
+//   {"timestamp":1690000000000,"label":"mnu_top_help_melee","nav":"MainWaitingForLoad","nav_after":"MainOnMelee","buttons":0}
+
+use serde::{ Deserialize, Serialize };
+use std::fmt;
+use std::fs::File;
+use std::io::{ self, BufRead, BufReader };
+use std::path::Path;
+
+use crate::nav_state::{ next_nav, CurrentNavigation };
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceEntry {
+    pub timestamp: u64,
+    pub label: String,
+    // The navigation state when the label was seen, and the state after navigation handled it.
+    pub nav: String,
+    pub nav_after: String,
+    // The buttons most recently injected by the npad hook.
+    pub buttons: u64,
+}
+
+#[derive(Debug)]
+pub enum TraceError {
+    Io(io::Error),
+    Malformed { line: usize, message: String },
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceError::Io(e) => write!(f, "couldn't read trace: {}", e),
+            TraceError::Malformed { line, message } => write!(f, "malformed trace line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+impl From<io::Error> for TraceError {
+    fn from(e: io::Error) -> Self {
+        TraceError::Io(e)
+    }
+}
+
+pub fn parse_trace<R: BufRead>(reader: R) -> Result<Vec<TraceEntry>, TraceError> {
+    let mut entries = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line).map_err(|e| TraceError::Malformed {
+            line: index + 1,
+            message: e.to_string(),
+        })?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+pub fn read_trace<P: AsRef<Path>>(path: P) -> Result<Vec<TraceEntry>, TraceError> {
+    parse_trace(BufReader::new(File::open(path)?))
+}
+
+// One step of a navigation fixture: seeing `label` while in `nav` should leave navigation in `expected_nav`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixtureStep {
+    pub label: String,
+    pub nav: String,
+    pub expected_nav: String,
+}
+
+// Turns a trace into a navigation fixture, collapsing consecutive repeats of the same label in the same state
+// since the game fetches some labels many times per screen.
+pub fn to_fixture(entries: &[TraceEntry]) -> Vec<FixtureStep> {
+    let mut steps: Vec<FixtureStep> = Vec::new();
+    for entry in entries {
+        let step = FixtureStep {
+            label: entry.label.clone(),
+            nav: entry.nav.clone(),
+            expected_nav: entry.nav_after.clone(),
+        };
+        if steps.last() != Some(&step) {
+            steps.push(step);
+        }
+    }
+    steps
+}
+
+// A fixture step where `nav_state::next_nav` no longer does what the trace recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureMismatch {
+    pub step: usize,
+    pub label: String,
+    pub nav: String,
+    pub expected_nav: String,
+    // None if a state name in the step isn't a `CurrentNavigation` any more.
+    pub actual_nav: Option<String>,
+}
+
+// Replays each step from its recorded state through `nav_state::next_nav`. Only the transition itself is checked:
+// state changes made by the actions (finishing the queue backing out in daemon mode, say) or by other hooks show up
+// as the next step's recorded `nav`, and the in-game labels we wait for GO! on aren't traced.
+pub fn replay_fixture(steps: &[FixtureStep]) -> Vec<FixtureMismatch> {
+    let mut mismatches = Vec::new();
+    for (index, step) in steps.iter().enumerate() {
+        let actual_nav = CurrentNavigation::from_name(&step.nav)
+            .map(|nav| format!("{:?}", next_nav(&step.label, nav, || false).nav));
+        if actual_nav.as_deref() != Some(step.expected_nav.as_str()) {
+            mismatches.push(FixtureMismatch {
+                step: index,
+                label: step.label.clone(),
+                nav: step.nav.clone(),
+                expected_nav: step.expected_nav.clone(),
+                actual_nav,
+            });
+        }
+    }
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    // A trace of searching for one replay that came back with no results, then one that played through.
+    const RUN: &str = r#"{"timestamp":1000,"label":"mnu_top_help_melee","nav":"MainWaitingForLoad","nav_after":"MainOnMelee","buttons":0}
+{"timestamp":1016,"label":"mnu_top_help_melee","nav":"MainOnMelee","nav_after":"MainOnMelee","buttons":0}
+{"timestamp":1033,"label":"mnu_top_help_melee","nav":"MainOnMelee","nav_after":"MainOnMelee","buttons":0}
+{"timestamp":1200,"label":"mnu_top_help_spirits","nav":"MainOnMelee","nav_after":"MainOnSpirits","buttons":32768}
+{"timestamp":1400,"label":"mnu_top_help_online","nav":"MainOnSpirits","nav_after":"MainOnOnline","buttons":16384}
+{"timestamp":1600,"label":"pop_online_connecting","nav":"MainOnOnline","nav_after":"MainWaitingOnline","buttons":1}
+
+{"timestamp":3000,"label":"mnu_onl_top_help_onl_melee","nav":"MainWaitingOnline","nav_after":"MainInOnline","buttons":0}
+{"timestamp":3200,"label":"mnu_onl_top_help_contribution","nav":"MainInOnline","nav_after":"MainOnSharedContent","buttons":32768}
+{"timestamp":3400,"label":"mnu_share_cat_movie","nav":"ScWaitingForLoad","nav_after":"ScSearchSubmenuTop","buttons":4}
+{"timestamp":3600,"label":"mnu_share_cat_movie","nav":"ScSearchSubmenuBottom","nav_after":"ScSearchSubmenuBottom","buttons":1}
+{"timestamp":4000,"label":"pop_share_no_post","nav":"ScSearchResults","nav_after":"ScNoResult","buttons":0}
+{"timestamp":4016,"label":"pop_share_no_post","nav":"ScNoResult","nav_after":"ScNoResult","buttons":2}
+{"timestamp":4300,"label":"mnu_share_cat_movie","nav":"ScNoResult","nav_after":"ScSearchSubmenuBottom","buttons":2}
+{"timestamp":4800,"label":"mnu_share_search_result_title","nav":"ScSearchResults","nav_after":"ScHoverReplay","buttons":0}
+{"timestamp":5000,"label":"pop_online_communicating_mini","nav":"ScHoverReplay","nav_after":"ScWaitingForGame","buttons":1}
+{"timestamp":90000,"label":"mnu_share_cat_movie","nav":"ScPlayback","nav_after":"ScSearchSubmenuBottom","buttons":2}
+"#;
+
+    #[test]
+    fn replays_a_recorded_run() {
+        let entries = parse_trace(Cursor::new(RUN)).unwrap();
+        assert_eq!(entries.len(), 16);
+        let fixture = to_fixture(&entries);
+        // The two repeated "mnu_top_help_melee" fetches on MainOnMelee collapse into one step.
+        assert_eq!(fixture.len(), 15);
+        assert_eq!(replay_fixture(&fixture), vec![]);
+    }
+
+    #[test]
+    fn reports_steps_that_no_longer_match() {
+        let fixture = vec![
+            FixtureStep {
+                label: "pop_share_replay_version_error".to_string(),
+                nav: "ScHoverReplay".to_string(),
+                expected_nav: "ScHoverReplay".to_string(),
+            },
+            FixtureStep {
+                label: "mnu_top_help_melee".to_string(),
+                nav: "MainGone".to_string(),
+                expected_nav: "MainOnMelee".to_string(),
+            },
+        ];
+        let mismatches = replay_fixture(&fixture);
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(mismatches[0].actual_nav.as_deref(), Some("ScIncompatible"));
+        assert_eq!(mismatches[1].step, 1);
+        assert_eq!(mismatches[1].actual_nav, None);
+    }
+
+    #[test]
+    fn reports_malformed_lines() {
+        let trace = "{\"timestamp\":1,\"label\":\"a\",\"nav\":\"b\",\"nav_after\":\"b\",\"buttons\":0}\nnot json\n";
+        match parse_trace(Cursor::new(trace)) {
+            Err(TraceError::Malformed { line, .. }) => assert_eq!(line, 2),
+            other => panic!("expected a malformed line, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+// Checks that a match log looks like something the plugin wrote in one piece.
+
+use std::fmt;
+
+use crate::reader::{ LogError, Match };
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+    // A line that didn't match the log schema.
+    Schema { line: usize, message: String },
+    Truncated { line: usize },
+    // The frame timer jumped by something other than one frame.
+    Discontinuity { frame: usize, from: u32, to: u32 },
+    // A frame that doesn't have a record for every fighter.
+    FighterCount { frame: usize, expected: usize, found: usize },
+    Empty,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::Schema { line, message } => write!(f, "line {}: {}", line, message),
+            Issue::Truncated { line } => write!(f, "line {}: truncated", line),
+            Issue::Discontinuity { frame, from, to } => {
+                write!(f, "frame {}: timer went from {} to {}", frame, from, to)
+            }
+            Issue::FighterCount { frame, expected, found } => {
+                write!(f, "frame {}: expected {} fighters, found {}", frame, expected, found)
+            }
+            Issue::Empty => write!(f, "no frames"),
+        }
+    }
+}
+
+pub fn validate(log: &Match) -> Vec<Issue> {
+    let mut issues: Vec<Issue> = log
+        .errors
+        .iter()
+        .filter_map(|e| match e {
+            LogError::Malformed { line, message } => Some(Issue::Schema { line: *line, message: message.clone() }),
+            LogError::Truncated { line } => Some(Issue::Truncated { line: *line }),
+            LogError::Io(_) => None,
+        })
+        .collect();
+
+    if log.frames.is_empty() {
+        issues.push(Issue::Empty);
+        return issues;
+    }
+
+    // Matches without a time limit never move the timer, so there's nothing to check continuity against.
+    let timed = log.frames.iter().any(|frame| frame.num_frames_left != log.frames[0].num_frames_left);
+    if timed {
+        for pair in log.frames.windows(2) {
+            let (from, to) = (pair[0].num_frames_left, pair[1].num_frames_left);
+            if from.checked_sub(1) != Some(to) {
+                issues.push(Issue::Discontinuity { frame: pair[1].index, from, to });
+            }
+        }
+    }
+
+    let expected = log.header.fighters.len();
+    for frame in &log.frames {
+        if frame.fighters.len() != expected {
+            issues.push(Issue::FighterCount { frame: frame.index, expected, found: frame.fighters.len() });
+        }
+    }
+
+    issues
+}
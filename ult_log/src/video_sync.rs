@@ -0,0 +1,130 @@
+// Lines log frames up with frames of a video captured while the replay played.
+//
+// The plugin logs a "sync" event on the first frame of the match, the same frame GO! appears on screen, and a
+// monotonic timestamp for every frame. Given when that frame shows up in the video and the video's own frame
+// timestamps, each log frame is matched to the video frame shown closest to when it happened. Logs from before
+// timestamps were recorded are assumed to run at a steady 60fps.
+//
+// Video timestamps are one time in seconds per line, like the output of
+//   ffprobe -select_streams v -show_entries frame=pts_time -of csv=p=0 capture.mp4
+
+use serde::{ Deserialize, Serialize };
+use std::fmt;
+use std::fs::File;
+use std::io::{ self, BufRead, BufReader };
+use std::path::Path;
+
+use crate::reader::Match;
+
+const FRAMES_PER_SECOND: f64 = 60.0;
+
+#[derive(Debug)]
+pub enum SyncError {
+    Io(io::Error),
+    NoVideoFrames,
+    NoLogFrames,
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::Io(e) => write!(f, "couldn't read video timestamps: {}", e),
+            SyncError::NoVideoFrames => write!(f, "no video frame timestamps found"),
+            SyncError::NoLogFrames => write!(f, "log has no frames"),
+        }
+    }
+}
+
+impl std::error::Error for SyncError {}
+
+impl From<io::Error> for SyncError {
+    fn from(e: io::Error) -> Self {
+        SyncError::Io(e)
+    }
+}
+
+// Lines that aren't a number, like a header, are skipped. The timestamps are sorted, since some containers list
+// frames in decode order.
+pub fn parse_video_times<R: BufRead>(reader: R) -> Result<Vec<f64>, SyncError> {
+    let mut times = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if let Ok(time) = line.trim().trim_end_matches(',').parse::<f64>() {
+            times.push(time);
+        }
+    }
+    if times.is_empty() {
+        return Err(SyncError::NoVideoFrames);
+    }
+    times.sort_by(|a, b| a.total_cmp(b));
+    Ok(times)
+}
+
+pub fn read_video_times<P: AsRef<Path>>(path: P) -> Result<Vec<f64>, SyncError> {
+    parse_video_times(BufReader::new(File::open(path)?))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FrameMapping {
+    pub log_frame: usize,
+    pub video_frame: usize,
+    // When the log frame should have been on screen, in video time.
+    pub video_time: f64,
+    // How far the matched video frame is from that, in milliseconds.
+    pub error_ms: f64,
+}
+
+// The log frame with the sync event, falling back to the first frame.
+pub fn sync_frame(log: &Match) -> Option<usize> {
+    log.frames
+        .iter()
+        .find(|frame| frame.events.iter().any(|event| event.event == "sync"))
+        .or(log.frames.first())
+        .map(|frame| frame.index)
+}
+
+// The video frame closest to `time`, if `time` is within the video.
+fn nearest_frame(video_times: &[f64], time: f64) -> Option<usize> {
+    let last = *video_times.last()?;
+    if time < video_times[0] || time > last {
+        return None;
+    }
+    let after = video_times.partition_point(|video_time| *video_time < time);
+    if after == 0 {
+        return Some(0);
+    }
+    if after == video_times.len() || time - video_times[after - 1] <= video_times[after] - time {
+        Some(after - 1)
+    } else {
+        Some(after)
+    }
+}
+
+// Maps every log frame that falls within the video. `sync_at` is the video time, in seconds, of the frame GO!
+// appears on.
+pub fn map_frames(log: &Match, video_times: &[f64], sync_at: f64) -> Result<Vec<FrameMapping>, SyncError> {
+    if video_times.is_empty() {
+        return Err(SyncError::NoVideoFrames);
+    }
+    let sync_index = sync_frame(log).ok_or(SyncError::NoLogFrames)?;
+    let sync_timestamp = log.frames[sync_index].timestamp_us();
+
+    Ok(log
+        .frames
+        .iter()
+        .filter_map(|frame| {
+            let since_sync = match (frame.timestamp_us(), sync_timestamp) {
+                (Some(timestamp), Some(sync_timestamp)) => (timestamp as f64 - sync_timestamp as f64) / 1_000_000.0,
+                _ => (frame.index as f64 - sync_index as f64) / FRAMES_PER_SECOND,
+            };
+            let video_time = sync_at + since_sync;
+            let video_frame = nearest_frame(video_times, video_time)?;
+            Some(FrameMapping {
+                log_frame: frame.index,
+                video_frame,
+                video_time,
+                error_ms: (video_times[video_frame] - video_time) * 1000.0,
+            })
+        })
+        .collect())
+}